@@ -1,6 +1,6 @@
 use rusty_eventide::{consumer::Consumer, messaging::HandleError, *};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct EventHandler;
 
 impl EventHandler {
@@ -1,7 +1,32 @@
 use std::time::Duration;
 
+pub mod closure;
+pub mod combinator;
 pub mod constant;
+pub mod exponential;
+pub mod fibonacci;
+pub mod linear;
+pub mod on_empty;
+
+use combinator::{Jittered, Max, Min};
 
 pub trait BackOff {
     fn duration(&mut self, iteration_message_count: u64) -> Duration;
 }
+
+/// Composes back-offs, e.g. `LinearBackOff::build().min(ConstantBackOff::build()).with_jitter(0.5)`
+pub trait BackOffExt: BackOff + Sized {
+    fn min<B: BackOff>(self, other: B) -> Min<Self, B> {
+        Min::new(self, other)
+    }
+
+    fn max<B: BackOff>(self, other: B) -> Max<Self, B> {
+        Max::new(self, other)
+    }
+
+    fn with_jitter(self, fraction: f64) -> Jittered<Self> {
+        Jittered::new(self, fraction)
+    }
+}
+
+impl<B: BackOff> BackOffExt for B {}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use crate::back_off::BackOff;
+
+/// Leaves the wait to a closure given the iteration's message count, for one-off strategies
+/// that don't warrant a type of their own
+pub struct ClosureBackOff<F: FnMut(u64) -> Duration> {
+    duration: F,
+}
+
+impl<F: FnMut(u64) -> Duration> ClosureBackOff<F> {
+    pub fn new(duration: F) -> Self {
+        Self { duration }
+    }
+}
+
+impl<F: FnMut(u64) -> Duration> std::fmt::Debug for ClosureBackOff<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ClosureBackOff")
+    }
+}
+
+impl<F: FnMut(u64) -> Duration> BackOff for ClosureBackOff<F> {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        (self.duration)(iteration_message_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_wait_as_long_as_the_closure_says() {
+        let mut back_off = ClosureBackOff::new(|count| Duration::from_millis(100 / (count + 1)));
+
+        assert_eq!(back_off.duration(0), Duration::from_millis(100));
+        assert_eq!(back_off.duration(3), Duration::from_millis(25));
+    }
+}
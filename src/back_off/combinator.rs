@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use rand::{thread_rng, Rng};
+
+use crate::back_off::BackOff;
+
+/// The shorter of two back-offs, both of which see every iteration
+#[derive(Debug)]
+pub struct Min<A: BackOff, B: BackOff> {
+    first: A,
+    second: B,
+}
+
+impl<A: BackOff, B: BackOff> Min<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: BackOff, B: BackOff> BackOff for Min<A, B> {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        let first = self.first.duration(iteration_message_count);
+        let second = self.second.duration(iteration_message_count);
+
+        first.min(second)
+    }
+}
+
+/// The longer of two back-offs, both of which see every iteration
+#[derive(Debug)]
+pub struct Max<A: BackOff, B: BackOff> {
+    first: A,
+    second: B,
+}
+
+impl<A: BackOff, B: BackOff> Max<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: BackOff, B: BackOff> BackOff for Max<A, B> {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        let first = self.first.duration(iteration_message_count);
+        let second = self.second.duration(iteration_message_count);
+
+        first.max(second)
+    }
+}
+
+/// Shortens each wait by a random part of up to `fraction` of it, `1.0` being full jitter.
+/// `fraction` is kept between 0 and 1, NaN being taken as 0.
+#[derive(Debug)]
+pub struct Jittered<B: BackOff> {
+    back_off: B,
+    fraction: f64,
+}
+
+impl<B: BackOff> Jittered<B> {
+    pub fn new(back_off: B, fraction: f64) -> Self {
+        Self {
+            back_off,
+            // NaN would otherwise pass through the clamp and panic when sampling
+            fraction: if fraction.is_nan() {
+                0.0
+            } else {
+                fraction.clamp(0.0, 1.0)
+            },
+        }
+    }
+}
+
+impl<B: BackOff> BackOff for Jittered<B> {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        let duration = self.back_off.duration(iteration_message_count);
+        let scale = thread_rng().gen_range(1.0 - self.fraction..=1.0);
+
+        duration.mul_f64(scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_off::{constant::ConstantBackOff, linear::LinearBackOff, BackOffExt};
+
+    fn linear() -> LinearBackOff {
+        LinearBackOff::new(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            Duration::from_millis(100),
+        )
+    }
+
+    fn constant(millis: u64) -> ConstantBackOff {
+        ConstantBackOff::new_with_duration(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn should_take_the_shorter_wait() {
+        let mut back_off = linear().min(constant(25));
+
+        let durations: Vec<_> = (0..4).map(|_| back_off.duration(0).as_millis()).collect();
+
+        assert_eq!(durations, vec![10, 20, 25, 25]);
+    }
+
+    #[test]
+    fn should_take_the_longer_wait() {
+        let mut back_off = linear().max(constant(25));
+
+        let durations: Vec<_> = (0..4).map(|_| back_off.duration(0).as_millis()).collect();
+
+        assert_eq!(durations, vec![25, 25, 30, 40]);
+    }
+
+    #[test]
+    fn should_keep_jitter_within_the_fraction() {
+        let mut back_off = constant(100).with_jitter(0.5);
+
+        for _ in 0..20 {
+            let duration = back_off.duration(0);
+            assert!(duration >= Duration::from_millis(50));
+            assert!(duration <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn should_leave_the_wait_alone_without_jitter() {
+        let mut back_off = constant(100).with_jitter(0.0);
+
+        assert_eq!(back_off.duration(0), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn should_take_a_nan_fraction_as_no_jitter() {
+        let mut back_off = constant(100).with_jitter(f64::NAN);
+
+        assert_eq!(back_off.duration(0), Duration::from_millis(100));
+    }
+}
@@ -26,6 +26,12 @@ impl ConstantBackOff {
     }
 }
 
+impl Default for ConstantBackOff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BackOff for ConstantBackOff {
     fn duration(&mut self, _iteration_message_count: u64) -> Duration {
         self.duration
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use rand::{thread_rng, Rng};
+
+use crate::back_off::BackOff;
+
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+
+/// How much of each wait is randomized, so many idle consumers don't poll in lockstep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jitter {
+    None,
+    /// Anywhere between zero and the exponential wait
+    Full,
+    /// Anywhere between the initial wait and `multiplier` times the previous wait
+    Decorrelated,
+}
+
+/// Waits nothing while messages are flowing, then `initial` after the first empty poll, growing
+/// by `multiplier` with each consecutive empty poll up to `max`
+#[derive(Debug)]
+pub struct ExponentialBackOff {
+    initial: Duration,
+    multiplier: f64,
+    max: Duration,
+    jitter: Jitter,
+    next: Duration,
+    previous: Duration,
+}
+
+impl ExponentialBackOff {
+    pub fn new() -> Self {
+        // Chosen to allow test to run quickly but still have some back off
+        Self::new_with(
+            Duration::from_millis(1),
+            DEFAULT_MULTIPLIER,
+            Duration::from_millis(10),
+        )
+    }
+
+    /// `multiplier` is kept at 1 or more so the wait never shrinks, NaN being taken as 1
+    pub fn new_with(initial: Duration, multiplier: f64, max: Duration) -> Self {
+        Self {
+            initial,
+            // `f64::max` also turns NaN into 1
+            multiplier: multiplier.max(1.0),
+            max,
+            jitter: Jitter::None,
+            next: initial,
+            previous: initial,
+        }
+    }
+
+    pub fn build() -> Self {
+        Self::new_with(
+            Duration::from_millis(100),
+            DEFAULT_MULTIPLIER,
+            Duration::from_secs(5),
+        )
+    }
+
+    pub fn jitter_strategy(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    fn reset(&mut self) {
+        self.next = self.initial;
+        self.previous = self.initial;
+    }
+
+    fn multiply(&self, duration: Duration) -> Duration {
+        Duration::try_from_secs_f64(duration.as_secs_f64() * self.multiplier)
+            .unwrap_or(self.max)
+            .min(self.max)
+    }
+
+    fn jitter(&self, duration: Duration) -> Duration {
+        match self.jitter {
+            Jitter::None => duration,
+            Jitter::Full => thread_rng().gen_range(Duration::ZERO..=duration),
+            Jitter::Decorrelated => {
+                let upper = self.multiply(self.previous).max(self.initial);
+                thread_rng().gen_range(self.initial.min(upper)..=upper)
+            }
+        }
+    }
+}
+
+impl Default for ExponentialBackOff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackOff for ExponentialBackOff {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        if iteration_message_count > 0 {
+            self.reset();
+            return Duration::ZERO;
+        }
+
+        let duration = self.jitter(self.next.min(self.max));
+        self.previous = duration;
+        self.next = self.multiply(self.next);
+
+        duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn back_off() -> ExponentialBackOff {
+        ExponentialBackOff::new_with(Duration::from_millis(10), 2.0, Duration::from_millis(50))
+    }
+
+    #[test]
+    fn should_not_wait_while_messages_are_flowing() {
+        let mut back_off = back_off();
+
+        assert_eq!(back_off.duration(1), Duration::ZERO);
+    }
+
+    #[test]
+    fn should_grow_on_consecutive_empty_polls_up_to_the_max() {
+        let mut back_off = back_off();
+
+        let durations: Vec<_> = (0..5).map(|_| back_off.duration(0).as_millis()).collect();
+
+        assert_eq!(durations, vec![10, 20, 40, 50, 50]);
+    }
+
+    #[test]
+    fn should_start_over_once_messages_arrive() {
+        let mut back_off = back_off();
+        back_off.duration(0);
+        back_off.duration(0);
+
+        back_off.duration(1);
+
+        assert_eq!(back_off.duration(0), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn should_never_shrink_the_wait() {
+        for multiplier in [0.5, -2.0, f64::NAN] {
+            let mut back_off = ExponentialBackOff::new_with(
+                Duration::from_millis(10),
+                multiplier,
+                Duration::from_millis(50),
+            );
+
+            let durations: Vec<_> = (0..3).map(|_| back_off.duration(0).as_millis()).collect();
+
+            assert_eq!(durations, vec![10, 10, 10]);
+        }
+    }
+
+    #[test]
+    fn should_keep_full_jitter_within_the_exponential_wait() {
+        let mut back_off = back_off().jitter_strategy(Jitter::Full);
+
+        for expected_max in [10, 20, 40, 50, 50] {
+            let duration = back_off.duration(0);
+            assert!(duration <= Duration::from_millis(expected_max));
+        }
+    }
+
+    #[test]
+    fn should_compose_with_jitter_on_top_of_its_own() {
+        use crate::back_off::BackOffExt;
+
+        let mut back_off = back_off().jitter_strategy(Jitter::Full).with_jitter(0.5);
+
+        assert!(back_off.duration(0) <= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn should_keep_decorrelated_jitter_between_the_initial_and_max() {
+        let mut back_off = back_off().jitter_strategy(Jitter::Decorrelated);
+
+        for _ in 0..20 {
+            let duration = back_off.duration(0);
+            assert!(duration >= Duration::from_millis(10));
+            assert!(duration <= Duration::from_millis(50));
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use crate::back_off::BackOff;
+
+/// Waits nothing while messages are flowing, then `initial` times the Fibonacci sequence
+/// (1, 1, 2, 3, 5, ...) for consecutive empty polls, up to `max`
+#[derive(Debug)]
+pub struct FibonacciBackOff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+    next: Duration,
+}
+
+impl FibonacciBackOff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            current: initial,
+            next: initial,
+        }
+    }
+
+    pub fn build() -> Self {
+        Self::new(Duration::from_millis(100), Duration::from_secs(5))
+    }
+}
+
+impl BackOff for FibonacciBackOff {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        if iteration_message_count > 0 {
+            self.current = self.initial;
+            self.next = self.initial;
+            return Duration::ZERO;
+        }
+
+        let duration = self.current.min(self.max);
+        let following = self.current.saturating_add(self.next).min(self.max);
+        self.current = self.next.min(self.max);
+        self.next = following;
+
+        duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_follow_the_fibonacci_sequence_up_to_the_max_until_messages_arrive() {
+        let mut back_off =
+            FibonacciBackOff::new(Duration::from_millis(10), Duration::from_millis(60));
+
+        let durations: Vec<_> = [0, 0, 0, 0, 0, 0, 0, 1, 0]
+            .into_iter()
+            .map(|count| back_off.duration(count).as_millis())
+            .collect();
+
+        assert_eq!(durations, vec![10, 10, 20, 30, 50, 60, 60, 0, 10]);
+    }
+}
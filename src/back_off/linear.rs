@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use crate::back_off::BackOff;
+
+/// Waits nothing while messages are flowing, then `initial` after the first empty poll, growing
+/// by `step` with each consecutive empty poll up to `max`
+#[derive(Debug)]
+pub struct LinearBackOff {
+    initial: Duration,
+    step: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl LinearBackOff {
+    pub fn new(initial: Duration, step: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            step,
+            max,
+            next: initial,
+        }
+    }
+
+    pub fn build() -> Self {
+        Self::new(
+            Duration::from_millis(100),
+            Duration::from_millis(100),
+            Duration::from_secs(5),
+        )
+    }
+}
+
+impl BackOff for LinearBackOff {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        if iteration_message_count > 0 {
+            self.next = self.initial;
+            return Duration::ZERO;
+        }
+
+        let duration = self.next.min(self.max);
+        self.next = duration.saturating_add(self.step);
+
+        duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_grow_by_the_step_up_to_the_max_until_messages_arrive() {
+        let mut back_off = LinearBackOff::new(
+            Duration::from_millis(10),
+            Duration::from_millis(5),
+            Duration::from_millis(20),
+        );
+
+        let durations: Vec<_> = [0, 0, 0, 0, 1, 0]
+            .into_iter()
+            .map(|count| back_off.duration(count).as_millis())
+            .collect();
+
+        assert_eq!(durations, vec![10, 15, 20, 20, 0, 10]);
+    }
+}
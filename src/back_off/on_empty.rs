@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use crate::back_off::BackOff;
+
+/// Waits nothing while messages are flowing and `duration` after every empty poll
+#[derive(Debug)]
+pub struct OnEmptyBackOff {
+    duration: Duration,
+}
+
+impl OnEmptyBackOff {
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+
+    pub fn build() -> Self {
+        Self::new(Duration::from_millis(100))
+    }
+}
+
+impl BackOff for OnEmptyBackOff {
+    fn duration(&mut self, iteration_message_count: u64) -> Duration {
+        if iteration_message_count > 0 {
+            Duration::ZERO
+        } else {
+            self.duration
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_wait_after_an_empty_poll() {
+        let duration = Duration::from_millis(10);
+        let mut back_off = OnEmptyBackOff::new(duration);
+
+        assert_eq!(back_off.duration(1), Duration::ZERO);
+        assert_eq!(back_off.duration(0), duration);
+    }
+}
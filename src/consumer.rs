@@ -1,19 +1,25 @@
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
+use std::time::Duration;
 
 use crate::back_off::{constant::ConstantBackOff, BackOff};
 // use controls::handler;
 use crate::messaging::{postgres::Category, *};
 use crate::run_time::{RunTime, SubstituteRunTime, SystemRunTime};
+use crate::session::{Session, SessionPool};
 use crate::settings::*;
 use position_store::{postgres::PostgresPositionStore, PositionStore, SubstitutePositionStore};
 
+pub mod error_policy;
 pub mod position_store;
+mod subscription;
+
+use error_policy::{ErrorAction, ErrorPolicy, StopOnError};
+use subscription::Subscription;
 
-// TODO: maybe get defaults depending on what is fulfilling the get?
-pub(crate) const DEFAULT_POSITION: u64 = 1;
 pub(crate) const DEFAULT_POSITION_COUNTER: u64 = 0;
+const SUBSCRIBED: &str = "get to not be in use by the subscription thread";
 
 #[derive(Debug)]
 pub struct Consumer<G: Get, B: BackOff, R: RunTime, P: PositionStore> {
@@ -23,12 +29,15 @@ pub struct Consumer<G: Get, B: BackOff, R: RunTime, P: PositionStore> {
     handlers: Vec<Box<dyn Handler + Send>>,
     active: Arc<Mutex<bool>>,
     iterations: Arc<Mutex<u64>>,
-    get: G,
+    fetch_retries: Arc<Mutex<u64>>,
+    /// `None` while a subscription thread is fetching with it
+    get: Option<G>,
     back_off: B,
     position: u64,
     position_update_counter: u64,
     position_store: P,
     settings: Settings,
+    error_policy: Box<dyn ErrorPolicy + Send>,
 }
 
 impl Consumer<SubstituteGetter, ConstantBackOff, SubstituteRunTime, SubstitutePositionStore> {
@@ -36,18 +45,22 @@ impl Consumer<SubstituteGetter, ConstantBackOff, SubstituteRunTime, SubstitutePo
         category: &str,
     ) -> Consumer<SubstituteGetter, ConstantBackOff, SubstituteRunTime, SubstitutePositionStore>
     {
+        let get = SubstituteGetter::new(category);
+
         Consumer {
             run_time: SubstituteRunTime::new(),
             category: category.to_string(),
             handlers: Vec::new(),
             active: Arc::new(Mutex::new(true)),
             iterations: Arc::new(Mutex::new(0)),
-            get: SubstituteGetter::new(category),
+            fetch_retries: Arc::new(Mutex::new(0)),
+            position: get.beginning_position(),
+            get: Some(get),
             back_off: ConstantBackOff::new(),
-            position: DEFAULT_POSITION,
             position_update_counter: DEFAULT_POSITION_COUNTER,
             position_store: SubstitutePositionStore::new(),
             settings: Settings::new(),
+            error_policy: Box::new(StopOnError),
         }
     }
 }
@@ -56,18 +69,62 @@ impl Consumer<Category, ConstantBackOff, SystemRunTime, PostgresPositionStore> {
     pub fn build(
         category: &str,
     ) -> Consumer<Category, ConstantBackOff, SystemRunTime, PostgresPositionStore> {
+        //TODO: handle errors
+        Self::build_params(category, Settings::build().expect("settings to build"))
+    }
+
+    /// `settings.consumer_identifier` gives the consumer its own position stream
+    pub fn build_params(
+        category: &str,
+        settings: Settings,
+    ) -> Consumer<Category, ConstantBackOff, SystemRunTime, PostgresPositionStore> {
+        //TODO: handle errors
+        let get_session = Session::build_params(&settings).expect("session to build");
+        let position_store_session = Session::build_params(&settings).expect("session to build");
+
+        Self::build_sessions(category, settings, get_session, position_store_session)
+    }
+
+    /// Reads messages and stores positions through connections shared with everything else
+    /// using `pool`
+    pub fn build_pooled(
+        category: &str,
+        settings: Settings,
+        pool: &SessionPool,
+    ) -> Consumer<Category, ConstantBackOff, SystemRunTime, PostgresPositionStore> {
+        Self::build_sessions(category, settings, pool.session(), pool.session())
+    }
+
+    fn build_sessions(
+        category: &str,
+        settings: Settings,
+        get_session: Session,
+        position_store_session: Session,
+    ) -> Consumer<Category, ConstantBackOff, SystemRunTime, PostgresPositionStore> {
+        //TODO: handle errors
+        let get = Category::build_params(category, settings.clone(), get_session)
+            .expect("category to build");
+        let position_store = PostgresPositionStore::build_params(
+            category,
+            settings.consumer_identifier.as_deref(),
+            position_store_session,
+        )
+        .expect("position store to build");
+
         Consumer {
             run_time: SystemRunTime::build(),
             category: category.to_string(),
             handlers: Vec::new(),
             active: Arc::new(Mutex::new(true)),
             iterations: Arc::new(Mutex::new(0)),
-            get: Category::build(category).expect("category to build"), //TODO: handle error
+            fetch_retries: Arc::new(Mutex::new(0)),
+            position: get.beginning_position(),
+            get: Some(get),
             back_off: ConstantBackOff::build(),
-            position: DEFAULT_POSITION,
             position_update_counter: DEFAULT_POSITION_COUNTER,
-            position_store: PostgresPositionStore::build(category),
-            settings: Settings::build(),
+            position_store,
+            settings,
+            error_policy: Box::new(StopOnError),
         }
     }
 }
@@ -92,6 +149,12 @@ impl<
         self
     }
 
+    /// Decides whether a handler error stops the consumer, `StopOnError` unless set
+    pub fn with_error_policy<E: ErrorPolicy + Send + 'static>(mut self, error_policy: E) -> Self {
+        self.error_policy = Box::new(error_policy);
+        self
+    }
+
     pub fn with_back_off<B2: BackOff>(self, back_off: B2) -> Consumer<G, B2, R, P> {
         // Is there a better way to do this? where I only have to specify back_off?
         // can't use `..self` because B and B2 are different types :(
@@ -101,27 +164,63 @@ impl<
             handlers: self.handlers,
             active: self.active,
             iterations: self.iterations,
+            fetch_retries: self.fetch_retries,
             get: self.get,
             back_off,
             position: self.position,
             position_update_counter: self.position_update_counter,
             position_store: self.position_store,
             settings: self.settings,
+            error_policy: self.error_policy,
+        }
+    }
+
+    /// Replaces what messages are read from, e.g. a `messaging::postgres::Stream` to consume a
+    /// single stream rather than a category
+    pub fn with_get<G2: Get>(self, get: G2) -> Consumer<G2, B, R, P> {
+        Consumer {
+            run_time: self.run_time,
+            category: self.category,
+            handlers: self.handlers,
+            active: self.active,
+            iterations: self.iterations,
+            fetch_retries: self.fetch_retries,
+            position: get.beginning_position(),
+            get: Some(get),
+            back_off: self.back_off,
+            position_update_counter: self.position_update_counter,
+            position_store: self.position_store,
+            settings: self.settings,
+            error_policy: self.error_policy,
         }
     }
 
-    pub fn initialize(&mut self) {
-        self.position = self.position_store.get();
+    pub fn initialize(&mut self) -> Result<(), HandleError> {
+        // The stored position is the last message handled, so start with the one after it
+        self.position = self
+            .position_store
+            .get()?
+            .map(|position| position + 1)
+            .unwrap_or_else(|| self.get().beginning_position());
         log::debug!("Starting at position: {}", self.position);
+
+        Ok(())
     }
 
     pub fn start(mut self) -> ConsumerHandle<G, B, R, P> {
         let active = self.active.clone();
         let iterations = self.iterations.clone();
+        let fetch_retries = self.fetch_retries.clone();
 
         // TODO: Should be controlled by RunTime somehow???
         let handle = std::thread::spawn(move || -> Result<Consumer<G, B, R, P>, HandleError> {
-            self.initialize();
+            self.settings
+                .validate()
+                .map_err(HandleError::from)
+                .and_then(|_| self.initialize())
+                .inspect_err(|_| {
+                    self.set_inactive();
+                })?;
 
             // Eventide has 3 main pieces that fulfill this loop, tick, and handle message
             //  - An actor (Actor) that handles triggering the subscription and sending messages to the consumer
@@ -149,6 +248,14 @@ impl<
             //        - triggers resupply on itself
             //  - A consumer (Not an Actor) that handles one message at a time and updates its position
 
+            // With `prefetch_queue_size` set a `Subscription` fetches ahead on its own thread,
+            // otherwise each tick fetches a batch and handles it before fetching the next
+            let subscription = self.settings.prefetch_queue_size.map(|queue_size| {
+                let get = self.get.take().expect(SUBSCRIBED);
+                let poll_interval = Duration::from_millis(self.settings.prefetch_poll_interval_ms);
+                Subscription::start(get, self.position, queue_size as usize, poll_interval)
+            });
+
             let mut should_continue = true;
             while should_continue {
                 let active = self.active.lock().expect("mutex to not be poisoned");
@@ -160,22 +267,42 @@ impl<
                 // Give the main thread a chance to lock the mutex
                 drop(active);
 
-                let iteration_message_count = self.tick().map_err(|error| {
-                    self.set_inactive();
-                    error
-                })?;
+                let result = match &subscription {
+                    Some(subscription) => self.tick_prefetched(subscription),
+                    None => self.tick(),
+                };
+
+                let iteration_message_count = match result {
+                    // The session has already run out of reconnect attempts, so keep fetching
+                    // from the same position on the following ticks rather than stopping
+                    Err(HandleError::GetError(error @ GetError::ConnectionLost(_))) => {
+                        self.record_fetch_retry(&error);
+                        0
+                    }
+                    result => result.inspect_err(|_| {
+                        self.set_inactive();
+                    })?,
+                };
 
                 let wait_time = self.back_off.duration(iteration_message_count);
 
-                self.run_time.sleep(wait_time);
+                // The subscription keeps the prefetch queue filled meanwhile, so only back off
+                // once it has run dry
+                if subscription.is_none() || iteration_message_count == 0 {
+                    self.run_time.sleep(wait_time);
+                }
                 should_continue = self.run_time.should_continue();
             }
 
+            if let Some(subscription) = subscription {
+                self.get = Some(subscription.stop());
+            }
+
             self.set_inactive();
             Ok(self)
         });
 
-        ConsumerHandle::build(active, iterations, handle)
+        ConsumerHandle::build(active, iterations, fetch_retries, handle)
     }
 
     fn set_inactive(&mut self) {
@@ -196,49 +323,113 @@ impl<
         *iterations += 1;
     }
 
+    /// How many fetches failed because the message store couldn't be reached and were retried
+    pub fn fetch_retries(&self) -> u64 {
+        *self.fetch_retries.lock().expect("mutex to not be poisoned")
+    }
+
+    fn record_fetch_retry(&mut self, error: &GetError) {
+        log::warn!("Unable to fetch messages, retrying: {}", error);
+
+        let mut fetch_retries = self.fetch_retries.lock().expect("mutex to not be poisoned");
+        *fetch_retries += 1;
+    }
+
     pub fn tick(&mut self) -> Result<u64, HandleError> {
         log::trace!("Tick for Position: {}", self.position);
 
         self.increment_iterations();
 
-        let messages = self.get.get(self.position as i64)?; //TODO: handle position
+        let position = self.position as i64;
+        let messages = self.get_mut().get(position)?; //TODO: handle position
         let messages_length = messages.len();
 
         for message_data in messages {
-            self.handle_message(message_data)?;
+            let position = self.get().message_position(&message_data);
+            self.handle_message(message_data, position)?;
+
+            if self.stopped() {
+                break;
+            }
         }
 
         Ok(messages_length as u64)
     }
 
+    /// Handles what the subscription has prefetched until the queue is empty or the consumer is
+    /// stopped
+    fn tick_prefetched(&mut self, subscription: &Subscription<G>) -> Result<u64, HandleError> {
+        log::trace!("Prefetched tick for Position: {}", self.position);
+
+        self.increment_iterations();
+
+        let mut messages_length = 0;
+        while let Some(prefetched) = subscription.next() {
+            let (position, message_data) = prefetched?;
+            self.handle_message(message_data, position)?;
+            messages_length += 1;
+
+            if self.stopped() {
+                break;
+            }
+        }
+
+        Ok(messages_length)
+    }
+
     // In Eventide this is the "consumer"
-    fn handle_message(&mut self, message_data: MessageData) -> Result<(), HandleError> {
+    fn handle_message(
+        &mut self,
+        message_data: MessageData,
+        position: u64,
+    ) -> Result<(), HandleError> {
         for handler in &mut self.handlers {
-            handler.handle(message_data.clone())?;
+            let mut attempt = 0;
+
+            while let Err(error) = handler.handle(message_data.clone()) {
+                attempt += 1;
+
+                match self
+                    .error_policy
+                    .error_raised(error, &message_data, attempt)
+                {
+                    ErrorAction::Stop(error) => return Err(error),
+                    ErrorAction::Retry(duration) => {
+                        self.run_time.sleep(duration);
+
+                        // Leave the message unhandled to be fetched again, rather than keep a
+                        // stopping consumer retrying for as long as the policy allows
+                        if !*self.active.lock().expect("mutex to not be poisoned") {
+                            return Ok(());
+                        }
+                    }
+                    ErrorAction::Skip => break,
+                }
+            }
         }
 
-        self.update_position(message_data.global_position);
-
-        Ok(())
+        self.update_position(position)
     }
 
-    fn update_position(&mut self, position: u64) {
+    fn update_position(&mut self, position: u64) -> Result<(), HandleError> {
         self.position = position + 1; // Set to get the next one on next fetch
 
         self.position_update_counter += 1;
 
         if self.position_update_counter >= self.settings.position_update_interval {
-            self.position_store.put(position);
+            self.position_store.put(position)?;
             self.position_update_counter = 0;
         }
+
+        Ok(())
     }
 
     pub fn get(&self) -> &G {
-        &self.get
+        self.get.as_ref().expect(SUBSCRIBED)
     }
 
     pub fn get_mut(&mut self) -> &mut G {
-        &mut self.get
+        self.get.as_mut().expect(SUBSCRIBED)
     }
 
     pub fn run_time_mut(&mut self) -> &mut R {
@@ -254,21 +445,26 @@ impl<
     }
 }
 
+type ConsumerResult<G, B, R, P> = Result<Consumer<G, B, R, P>, HandleError>;
+
 pub struct ConsumerHandle<G: Get, B: BackOff, R: RunTime, P: PositionStore> {
     active: Arc<Mutex<bool>>,
     iterations: Arc<Mutex<u64>>,
-    handle: Option<JoinHandle<Result<Consumer<G, B, R, P>, HandleError>>>,
+    fetch_retries: Arc<Mutex<u64>>,
+    handle: Option<JoinHandle<ConsumerResult<G, B, R, P>>>,
 }
 
 impl<G: Get, B: BackOff, R: RunTime, P: PositionStore> ConsumerHandle<G, B, R, P> {
     pub fn build(
         active: Arc<Mutex<bool>>,
         iterations: Arc<Mutex<u64>>,
+        fetch_retries: Arc<Mutex<u64>>,
         handle: JoinHandle<Result<Consumer<G, B, R, P>, HandleError>>,
     ) -> Self {
         Self {
             active,
             iterations,
+            fetch_retries,
             handle: Some(handle),
         }
     }
@@ -277,6 +473,10 @@ impl<G: Get, B: BackOff, R: RunTime, P: PositionStore> ConsumerHandle<G, B, R, P
         *self.iterations.lock().expect("mutex to not be poisoned")
     }
 
+    pub fn fetch_retries(&self) -> u64 {
+        *self.fetch_retries.lock().expect("mutex to not be poisoned")
+    }
+
     pub fn stop(&mut self) {
         let mut active = self.active.lock().expect("mutex to not be poisoned");
         *active = false;
@@ -571,6 +771,35 @@ mod unit_tests {
         assert_eq!(handler.message_count(), messages_count);
     }
 
+    #[test]
+    fn should_only_dispatch_messages_of_a_registered_type() {
+        init();
+
+        // Arrange
+        let handled_count = Arc::new(Mutex::new(0));
+        let callback_count = handled_count.clone();
+        let dispatcher = Dispatcher::build().on::<controls::messages::Example>(move |_, _| {
+            *callback_count.lock().expect("mutex to not be poisoned") += 1;
+            Ok(())
+        });
+
+        let mut consumer = Consumer::new("mycategory").add_handler(dispatcher);
+
+        let mut messages = controls::messages::example();
+        messages[1].message_type = "Unhandled".to_string();
+        consumer.get_mut().queue_messages(&messages);
+
+        // Act
+        let _ = consumer.tick();
+
+        // Assert
+        let only_one_message_handled = 1;
+        assert_eq!(
+            *handled_count.lock().expect("mutex to not be poisoned"),
+            only_one_message_handled
+        );
+    }
+
     #[test]
     fn should_stop_processing_messages_when_handler_errors_on_tick() {
         init();
@@ -589,6 +818,136 @@ mod unit_tests {
         assert_eq!(handler.message_count(), only_one_message_handled);
     }
 
+    #[test]
+    fn should_skip_messages_a_handler_fails_on_when_told_to() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::FailingHandler::build();
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_error_policy(error_policy::SkipOnError);
+
+        let messages = add_messages(&mut consumer);
+        let messages_count = messages.len() as u64;
+
+        // Act
+        let result = consumer.tick();
+
+        // Assert
+        assert_eq!(result.expect("tick to work"), messages_count);
+        assert_eq!(handler.message_count(), messages_count);
+        let next_position = messages.last().expect("messages").global_position + 1;
+        assert_eq!(consumer.position, next_position);
+    }
+
+    #[test]
+    fn should_retry_messages_a_handler_fails_on() {
+        init();
+
+        // Arrange
+        let failures = 2;
+        let handler = controls::handler::FlakyHandler::build(failures);
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_error_policy(error_policy::RetryOnError::new(
+                failures as u32,
+                ConstantBackOff::new,
+            ));
+
+        let messages = add_messages(&mut consumer);
+        let messages_count = messages.len() as u64;
+
+        // Act
+        let result = consumer.tick();
+
+        // Assert
+        assert_eq!(result.expect("tick to work"), messages_count);
+        assert_eq!(handler.message_count(), messages_count + failures);
+    }
+
+    #[test]
+    fn should_stop_once_out_of_retries() {
+        init();
+
+        // Arrange
+        let retries = 2;
+        let handler = controls::handler::FailingHandler::build();
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_error_policy(error_policy::RetryOnError::new(
+                retries,
+                ConstantBackOff::new,
+            ));
+
+        add_messages(&mut consumer);
+
+        // Act
+        let result = consumer.tick();
+
+        // Assert
+        assert!(result.is_err());
+        let first_attempt_and_retries = 1 + retries as u64;
+        assert_eq!(handler.message_count(), first_attempt_and_retries);
+    }
+
+    #[test]
+    fn should_stop_while_retrying_a_message() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::FailingHandler::build();
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_error_policy(error_policy::RetryOnError::new(
+                u32::MAX,
+                ConstantBackOff::new,
+            ));
+
+        add_messages(&mut consumer);
+
+        // Act
+        let mut consumer = consumer.start();
+        std::thread::sleep(std::time::Duration::from_millis(15));
+        consumer.stop();
+
+        // Assert
+        assert!(consumer.stopped());
+        let attempts = handler.message_count();
+        assert!(attempts > 1);
+        std::thread::sleep(std::time::Duration::from_millis(15));
+        assert_eq!(handler.message_count(), attempts);
+    }
+
+    #[test]
+    fn should_hand_handler_errors_to_error_raised() {
+        init();
+
+        // Arrange
+        let raised = Arc::new(Mutex::new(Vec::new()));
+        let recorded = raised.clone();
+        let handler = controls::handler::FailingHandler::build();
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_error_policy(error_policy::ErrorRaised::new(move |_error, message| {
+                recorded
+                    .lock()
+                    .expect("mutex to not be poisoned")
+                    .push(message.id.clone());
+                Ok(())
+            }));
+
+        let messages = add_messages(&mut consumer);
+
+        // Act
+        let result = consumer.tick();
+
+        // Assert
+        assert!(result.is_ok());
+        let expected: Vec<_> = messages.iter().map(|message| message.id.clone()).collect();
+        assert_eq!(*raised.lock().expect("mutex to not be poisoned"), expected);
+    }
+
     /////////////////////
     // Position
     /////////////////////
@@ -629,10 +988,10 @@ mod unit_tests {
         let messages_count = messages.len() as u64;
 
         let position_store = consumer.position_store_mut();
-        position_store.set_position(messages_count + 1); // One after the queued messages
+        position_store.set_position(messages_count); // The last of the queued messages
 
         // Act
-        consumer.initialize();
+        consumer.initialize().expect("initialize to work");
 
         let _ = consumer.tick();
 
@@ -641,6 +1000,174 @@ mod unit_tests {
         assert_eq!(handler.message_count(), no_messages_processed);
     }
 
+    #[test]
+    fn should_stop_processing_messages_when_position_cannot_be_stored() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::TrackingHandler::build();
+        let mut settings = Settings::new();
+        settings.position_update_interval = 1;
+
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_settings(settings);
+
+        add_messages(&mut consumer);
+        consumer.position_store_mut().fail();
+
+        // Act
+        let result = consumer.tick();
+
+        // Assert
+        assert!(matches!(result, Err(HandleError::PositionStoreError(_))));
+        let only_one_message_handled = 1;
+        assert_eq!(handler.message_count(), only_one_message_handled);
+    }
+
+    #[test]
+    fn should_stop_on_start_when_position_cannot_be_retrieved() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::TrackingHandler::build();
+        let mut consumer = Consumer::new("mycategory").add_handler(handler.clone());
+
+        add_messages(&mut consumer);
+        consumer.position_store_mut().fail();
+
+        // Act
+        let consumer_handle = consumer.start();
+        let result = consumer_handle.wait();
+
+        // Assert
+        assert!(matches!(result, Err(HandleError::PositionStoreError(_))));
+        let no_messages_processed = 0;
+        assert_eq!(handler.message_count(), no_messages_processed);
+    }
+
+    #[test]
+    fn should_stop_on_start_when_settings_are_invalid() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::TrackingHandler::build();
+        let mut settings = Settings::new();
+        settings.consumer_group_member = Some(0);
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_settings(settings);
+
+        add_messages(&mut consumer);
+
+        // Act
+        let consumer_handle = consumer.start();
+        let result = consumer_handle.wait();
+
+        // Assert
+        assert!(matches!(result, Err(HandleError::InvalidSettings(_))));
+        let no_messages_processed = 0;
+        assert_eq!(handler.message_count(), no_messages_processed);
+    }
+
+    #[test]
+    fn should_handle_prefetched_messages_until_stopped() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::TrackingHandler::build();
+        let mut settings = Settings::new();
+        settings.prefetch_queue_size = Some(1);
+        settings.prefetch_poll_interval_ms = 1;
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_settings(settings);
+
+        let messages = add_messages(&mut consumer);
+        let messages_count = messages.len() as u64;
+
+        // Act
+        let mut consumer_handle = consumer.start();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while handler.message_count() < messages_count {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "prefetched messages were not handled in time"
+            );
+            std::thread::yield_now();
+        }
+        consumer_handle.stop();
+
+        // Assert
+        assert!(consumer_handle.stopped());
+        assert_eq!(handler.message_count(), messages_count);
+    }
+
+    #[test]
+    fn should_keep_fetching_when_the_connection_is_lost() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::TrackingHandler::build();
+        let mut consumer = Consumer::new("mycategory").add_handler(handler.clone());
+
+        let messages = add_messages(&mut consumer);
+        let messages_count = messages.len() as u64;
+        let lost_connections = 2;
+        consumer.get_mut().lose_connection(lost_connections);
+
+        // Act
+        let mut consumer_handle = consumer.start();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while handler.message_count() < messages_count {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "messages were not handled in time"
+            );
+            std::thread::yield_now();
+        }
+        consumer_handle.stop();
+
+        // Assert
+        assert_eq!(handler.message_count(), messages_count);
+        assert_eq!(consumer_handle.fetch_retries(), lost_connections);
+    }
+
+    #[test]
+    fn should_keep_fetching_prefetched_messages_when_the_connection_is_lost() {
+        init();
+
+        // Arrange
+        let handler = controls::handler::TrackingHandler::build();
+        let mut settings = Settings::new();
+        settings.prefetch_queue_size = Some(1);
+        settings.prefetch_poll_interval_ms = 1;
+        let mut consumer = Consumer::new("mycategory")
+            .add_handler(handler.clone())
+            .with_settings(settings);
+
+        let messages = add_messages(&mut consumer);
+        let messages_count = messages.len() as u64;
+        let lost_connections = 2;
+        consumer.get_mut().lose_connection(lost_connections);
+
+        // Act
+        let mut consumer_handle = consumer.start();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while handler.message_count() < messages_count {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "prefetched messages were not handled in time"
+            );
+            std::thread::yield_now();
+        }
+        consumer_handle.stop();
+
+        // Assert
+        assert_eq!(handler.message_count(), messages_count);
+        assert_eq!(consumer_handle.fetch_retries(), lost_connections);
+    }
+
     #[test]
     fn should_start_at_default_global_position_with_no_position_stored() {
         init();
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use crate::back_off::BackOff;
+use crate::messaging::{HandleError, MessageData};
+
+/// What the consumer does after a handler fails on a message
+#[derive(Debug)]
+pub enum ErrorAction {
+    /// Stops the consumer with the error
+    Stop(HandleError),
+    /// Hands the message to the same handler again after waiting
+    Retry(Duration),
+    /// Moves on as though the handler had handled the message
+    Skip,
+}
+
+/// Decides how a consumer carries on when a handler fails, so one bad message doesn't have to
+/// halt the whole category
+pub trait ErrorPolicy: std::fmt::Debug {
+    /// `attempt` counts the handler's failures on this message so far, starting at 1
+    fn error_raised(
+        &mut self,
+        error: HandleError,
+        message: &MessageData,
+        attempt: u32,
+    ) -> ErrorAction;
+}
+
+/// Stops the consumer on the first error, the default
+#[derive(Debug, Default)]
+pub struct StopOnError;
+
+impl ErrorPolicy for StopOnError {
+    fn error_raised(
+        &mut self,
+        error: HandleError,
+        _message: &MessageData,
+        _attempt: u32,
+    ) -> ErrorAction {
+        ErrorAction::Stop(error)
+    }
+}
+
+/// Logs the error and moves past the message
+#[derive(Debug, Default)]
+pub struct SkipOnError;
+
+impl ErrorPolicy for SkipOnError {
+    fn error_raised(
+        &mut self,
+        error: HandleError,
+        message: &MessageData,
+        _attempt: u32,
+    ) -> ErrorAction {
+        log::error!(
+            "Skipping message {} at global position {}: {}",
+            message.id,
+            message.global_position,
+            error
+        );
+
+        ErrorAction::Skip
+    }
+}
+
+/// Retries a failing message up to `retries` times, waiting as long as a back-off says between
+/// attempts, then stops the consumer. Each failing message gets a fresh back-off from
+/// `new_back_off`, e.g. `RetryOnError::new(3, LinearBackOff::build)`, which sees each retry as an
+/// empty poll.
+pub struct RetryOnError<B: BackOff, F: FnMut() -> B> {
+    retries: u32,
+    new_back_off: F,
+    back_off: Option<B>,
+}
+
+impl<B: BackOff, F: FnMut() -> B> RetryOnError<B, F> {
+    pub fn new(retries: u32, new_back_off: F) -> Self {
+        Self {
+            retries,
+            new_back_off,
+            back_off: None,
+        }
+    }
+}
+
+impl<B: BackOff, F: FnMut() -> B> std::fmt::Debug for RetryOnError<B, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RetryOnError {{ retries: {} }}", self.retries)
+    }
+}
+
+impl<B: BackOff, F: FnMut() -> B> ErrorPolicy for RetryOnError<B, F> {
+    fn error_raised(
+        &mut self,
+        error: HandleError,
+        message: &MessageData,
+        attempt: u32,
+    ) -> ErrorAction {
+        if attempt > self.retries {
+            return ErrorAction::Stop(error);
+        }
+
+        if attempt == 1 {
+            self.back_off = Some((self.new_back_off)());
+        }
+        let back_off = self.back_off.get_or_insert_with(&mut self.new_back_off);
+
+        log::warn!(
+            "Retrying message {} at global position {} ({} of {}): {}",
+            message.id,
+            message.global_position,
+            attempt,
+            self.retries,
+            error
+        );
+
+        ErrorAction::Retry(back_off.duration(0))
+    }
+}
+
+/// Hands the error to `error_raised`, as Eventide's handlers do. Returning the error stops the
+/// consumer, returning `Ok` moves past the message.
+pub struct ErrorRaised<F: FnMut(HandleError, &MessageData) -> Result<(), HandleError>> {
+    error_raised: F,
+}
+
+impl<F: FnMut(HandleError, &MessageData) -> Result<(), HandleError>> ErrorRaised<F> {
+    pub fn new(error_raised: F) -> Self {
+        Self { error_raised }
+    }
+}
+
+impl<F: FnMut(HandleError, &MessageData) -> Result<(), HandleError>> std::fmt::Debug
+    for ErrorRaised<F>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ErrorRaised")
+    }
+}
+
+impl<F: FnMut(HandleError, &MessageData) -> Result<(), HandleError>> ErrorPolicy
+    for ErrorRaised<F>
+{
+    fn error_raised(
+        &mut self,
+        error: HandleError,
+        message: &MessageData,
+        _attempt: u32,
+    ) -> ErrorAction {
+        match (self.error_raised)(error, message) {
+            Ok(()) => ErrorAction::Skip,
+            Err(error) => ErrorAction::Stop(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_off::linear::LinearBackOff;
+    use crate::controls;
+
+    fn error() -> HandleError {
+        HandleError::MissingHandler
+    }
+
+    fn message() -> MessageData {
+        controls::messages::example()
+            .into_iter()
+            .next()
+            .expect("a message")
+    }
+
+    #[test]
+    fn should_stop_on_the_first_error() {
+        let action = StopOnError.error_raised(error(), &message(), 1);
+
+        assert!(matches!(
+            action,
+            ErrorAction::Stop(HandleError::MissingHandler)
+        ));
+    }
+
+    #[test]
+    fn should_skip_the_message() {
+        let action = SkipOnError.error_raised(error(), &message(), 1);
+
+        assert!(matches!(action, ErrorAction::Skip));
+    }
+
+    #[test]
+    fn should_retry_with_the_back_off_until_out_of_retries() {
+        let back_off = || {
+            LinearBackOff::new(
+                Duration::from_millis(10),
+                Duration::from_millis(10),
+                Duration::from_millis(100),
+            )
+        };
+        let mut policy = RetryOnError::new(2, back_off);
+        let message = message();
+
+        let first = policy.error_raised(error(), &message, 1);
+        let second = policy.error_raised(error(), &message, 2);
+        let third = policy.error_raised(error(), &message, 3);
+        let next_message = policy.error_raised(error(), &message, 1);
+
+        assert!(matches!(first, ErrorAction::Retry(d) if d == Duration::from_millis(10)));
+        assert!(matches!(second, ErrorAction::Retry(d) if d == Duration::from_millis(20)));
+        assert!(matches!(third, ErrorAction::Stop(_)));
+        assert!(matches!(next_message, ErrorAction::Retry(d) if d == Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn should_let_error_raised_decide_whether_to_stop() {
+        let mut swallowing = ErrorRaised::new(|_error, _message| Ok(()));
+        let mut raising = ErrorRaised::new(|error, _message| Err(error));
+
+        let swallowed = swallowing.error_raised(error(), &message(), 1);
+        let raised = raising.error_raised(error(), &message(), 1);
+
+        assert!(matches!(swallowed, ErrorAction::Skip));
+        assert!(matches!(raised, ErrorAction::Stop(_)));
+    }
+}
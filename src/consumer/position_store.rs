@@ -1,14 +1,29 @@
 use serde_json::Value;
+use thiserror::Error;
 
 use std::collections::HashMap;
+use std::error::Error as StdError;
 
-use crate::consumer::DEFAULT_POSITION;
+use crate::messaging::{GetError, MessageError, WriteError};
 
 pub mod postgres;
 
 pub trait PositionStore: PositionStoreTelemetry + std::fmt::Debug {
-    fn get(&mut self) -> u64;
-    fn put(&mut self, position: u64); //TODO: should have result?
+    /// `None` when no position has been stored yet
+    fn get(&mut self) -> Result<Option<u64>, PositionStoreError>;
+    fn put(&mut self, position: u64) -> Result<(), PositionStoreError>;
+}
+
+#[derive(Error, Debug)]
+pub enum PositionStoreError {
+    #[error("Unable to get position: {0}")]
+    GetError(#[from] GetError),
+    #[error("Unable to put position: {0}")]
+    WriteError(#[from] WriteError),
+    #[error("Stored position is not valid: {0}")]
+    MessageError(#[from] MessageError),
+    #[error("An error storing position occurred: {0}")]
+    DataError(#[from] Box<dyn StdError + Send + Sync>),
 }
 
 pub trait PositionStoreTelemetry {
@@ -19,6 +34,7 @@ pub trait PositionStoreTelemetry {
 #[derive(Debug)]
 pub struct SubstitutePositionStore {
     position: Option<u64>,
+    failing: bool,
     telemetry: HashMap<String, Value>,
 }
 
@@ -26,6 +42,7 @@ impl SubstitutePositionStore {
     pub fn new() -> Self {
         Self {
             position: None,
+            failing: false,
             telemetry: HashMap::new(),
         }
     }
@@ -34,45 +51,57 @@ impl SubstitutePositionStore {
         self.position = Some(position);
     }
 
+    /// Makes every following get and put return an error
+    pub fn fail(&mut self) {
+        self.failing = true;
+    }
+
+    fn failure(&self) -> Result<(), PositionStoreError> {
+        if self.failing {
+            Err(
+                Box::<dyn StdError + Send + Sync>::from("Substitute position store set to fail")
+                    .into(),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn get_count(&self) -> u64 {
         self.telemetry
             .get(GET_COUNT_KEY)
-            .map(|value| {
-                if let Some(count) = value.as_u64() {
-                    count
-                } else {
-                    0
-                }
-            })
+            .map(|value| value.as_u64().unwrap_or_default())
             .unwrap_or(0)
     }
 
     pub fn put_count(&self) -> u64 {
         self.telemetry
             .get(PUT_COUNT_KEY)
-            .map(|value| {
-                if let Some(count) = value.as_u64() {
-                    count
-                } else {
-                    0
-                }
-            })
+            .map(|value| value.as_u64().unwrap_or_default())
             .unwrap_or(0)
     }
 }
 
+impl Default for SubstitutePositionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PositionStore for SubstitutePositionStore {
-    fn get(&mut self) -> u64 {
+    fn get(&mut self) -> Result<Option<u64>, PositionStoreError> {
         self.record_get();
-        self.position.unwrap_or(DEFAULT_POSITION)
+        self.failure()?;
+        Ok(self.position)
     }
-    fn put(&mut self, _position: u64) {
+    fn put(&mut self, _position: u64) -> Result<(), PositionStoreError> {
         self.record_put();
+        self.failure()
     }
 }
 
-const GET_COUNT_KEY: &'static str = "get_count";
-const PUT_COUNT_KEY: &'static str = "put_count";
+const GET_COUNT_KEY: &str = "get_count";
+const PUT_COUNT_KEY: &str = "put_count";
 
 impl PositionStoreTelemetry for SubstitutePositionStore {
     fn record_get(&mut self) {
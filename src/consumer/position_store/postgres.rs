@@ -1,27 +1,101 @@
-use super::{PositionStore, PositionStoreTelemetry};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-pub fn stream_name(category: &str) -> String {
-    "".to_string()
+use super::{PositionStore, PositionStoreError, PositionStoreTelemetry};
+use crate::{
+    messaging::{
+        postgres::{get_last_stream_message, write_message},
+        Message, StreamName, StreamNameError,
+    },
+    session::{Session, SessionError},
+};
+
+const POSITION_TYPE: &str = "position";
+
+/// Written to the position stream each time the consumer's position is stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recorded {
+    pub position: u64,
+}
+
+impl Message for Recorded {
+    const TYPE_NAME: &'static str = "Recorded";
+}
+
+/// The `category:position` stream the positions of the category's consumer are stored in, or
+/// `category:position-<consumer identifier>` when the consumer has an identifier
+pub fn stream_name(
+    category: &str,
+    consumer_identifier: Option<&str>,
+) -> Result<String, StreamNameError> {
+    let mut stream_name = StreamName::parse(category)?;
+
+    if !stream_name.has_type(POSITION_TYPE) {
+        stream_name = stream_name.with_type(POSITION_TYPE)?;
+    }
+
+    if let Some(consumer_identifier) = consumer_identifier {
+        stream_name = stream_name.with_id(consumer_identifier)?;
+    }
+
+    Ok(stream_name.to_string())
+}
+
+#[derive(Error, Debug)]
+pub enum PostgresPositionStoreError {
+    #[error("Session Error: {0}")]
+    SessionError(#[from] SessionError),
+    #[error("Invalid position stream name: {0}")]
+    StreamNameError(#[from] StreamNameError),
 }
 
 #[derive(Debug)]
 pub struct PostgresPositionStore {
-    category: String,
+    stream_name: String,
+    session: Session,
 }
 
 impl PostgresPositionStore {
-    pub fn build(category: impl Into<String>) -> Self {
-        Self {
-            category: category.into(),
-        }
+    pub fn build(category: impl Into<String>) -> Result<Self, PostgresPositionStoreError> {
+        Self::build_params(category, None, Session::build()?)
+    }
+
+    pub fn build_params(
+        category: impl Into<String>,
+        consumer_identifier: Option<&str>,
+        session: Session,
+    ) -> Result<Self, PostgresPositionStoreError> {
+        Ok(Self {
+            stream_name: stream_name(&category.into(), consumer_identifier)?,
+            session,
+        })
+    }
+
+    pub fn stream_name(&self) -> &str {
+        &self.stream_name
     }
 }
 
 impl PositionStore for PostgresPositionStore {
-    fn get(&mut self) -> u64 {
-        1
+    fn get(&mut self) -> Result<Option<u64>, PositionStoreError> {
+        self.record_get();
+
+        let message_data = get_last_stream_message(&mut self.session, &self.stream_name)?;
+
+        match message_data {
+            Some(message_data) => Ok(Some(Recorded::from_message_data(&message_data)?.position)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&mut self, position: u64) -> Result<(), PositionStoreError> {
+        self.record_put();
+
+        let message_data = Recorded { position }.to_write_message_data()?;
+        write_message(&mut self.session, &message_data, &self.stream_name, None)?;
+
+        Ok(())
     }
-    fn put(&mut self, _position: u64) {}
 }
 
 impl PositionStoreTelemetry for PostgresPositionStore {
@@ -30,10 +104,49 @@ impl PositionStoreTelemetry for PostgresPositionStore {
     fn record_put(&mut self) {}
 }
 
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn should_add_the_position_type_to_the_category() {
+        assert_eq!(
+            stream_name("account", None).expect("stream name to be valid"),
+            "account:position"
+        );
+        assert_eq!(
+            stream_name("account:command", None).expect("stream name to be valid"),
+            "account:command+position"
+        );
+    }
+
+    #[test]
+    fn should_not_add_the_position_type_twice() {
+        assert_eq!(
+            stream_name("account:position", None).expect("stream name to be valid"),
+            "account:position"
+        );
+    }
+
+    #[test]
+    fn should_use_the_consumer_identifier_as_the_stream_id() {
+        assert_eq!(
+            stream_name("account", Some("reporting")).expect("stream name to be valid"),
+            "account:position-reporting"
+        );
+    }
+
+    #[test]
+    fn should_reject_an_invalid_category() {
+        assert!(stream_name("", None).is_err());
+        assert!(stream_name("account", Some("")).is_err());
+    }
+}
+
 #[cfg(all(test, feature = "integration_tests"))]
 mod integration_tests {
     use super::*;
-    use crate::{controls, settings::Settings};
+    use crate::{controls, messaging::Metadata};
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -45,18 +158,92 @@ mod integration_tests {
 
         // Arrange
         let category = controls::category::unique_category();
-        let mut position_store = PostgresPositionStore::build(&category);
+        let mut position_store =
+            PostgresPositionStore::build(&category).expect("position store to build");
         let position = 1;
-        let postiion_stream_name = stream_name(&category);
+        let position_stream_name = stream_name(&category, None).expect("stream name to be valid");
 
         // Act
-        position_store.put(position);
+        position_store.put(position).expect("put to work");
 
         // Assert
-        // TODO: Read from the `category:position` stream (no consumer identity provided) and expect a `Recorded` event with a `position` attribute
+        let mut session = Session::build().expect("session to be built");
+        let message_data = get_last_stream_message(&mut session, &position_stream_name)
+            .expect("get to work")
+            .expect("a position to be recorded");
+
+        assert_eq!(message_data.stream_name, position_stream_name);
+        let recorded = Recorded::from_message_data(&message_data).expect("a Recorded message");
+        assert_eq!(recorded, Recorded { position });
+        assert_eq!(
+            Metadata::from_message_data(&message_data).expect("metadata to parse"),
+            Metadata::new()
+        );
     }
 
     #[test]
-    #[ignore]
-    fn should_use_consumer_identity_as_stream_identity_if_provided() {}
+    fn should_get_no_position_when_none_stored() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let mut position_store =
+            PostgresPositionStore::build(&category).expect("position store to build");
+
+        // Act
+        let position = position_store.get().expect("get to work");
+
+        // Assert
+        assert_eq!(position, None);
+    }
+
+    #[test]
+    fn should_get_the_last_stored_position() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let mut position_store =
+            PostgresPositionStore::build(&category).expect("position store to build");
+        position_store.put(11).expect("put to work");
+        position_store.put(22).expect("put to work");
+
+        // Act
+        let position = position_store.get().expect("get to work");
+
+        // Assert
+        assert_eq!(position, Some(22));
+    }
+
+    #[test]
+    fn should_use_consumer_identity_as_stream_identity_if_provided() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let consumer_identifier = "some_consumer";
+        let session = Session::build().expect("session to be built");
+        let mut position_store =
+            PostgresPositionStore::build_params(&category, Some(consumer_identifier), session)
+                .expect("position store to build");
+        let mut other_position_store =
+            PostgresPositionStore::build(&category).expect("position store to build");
+        let position = 1;
+
+        // Act
+        position_store.put(position).expect("put to work");
+
+        // Assert
+        let position_stream_name = format!("{}:position-{}", category, consumer_identifier);
+        assert_eq!(position_store.stream_name(), position_stream_name);
+
+        let mut session = Session::build().expect("session to be built");
+        let message_data = get_last_stream_message(&mut session, &position_stream_name)
+            .expect("get to work")
+            .expect("a position to be recorded");
+        let recorded = Recorded::from_message_data(&message_data).expect("a Recorded message");
+        assert_eq!(recorded, Recorded { position });
+
+        assert_eq!(other_position_store.get().expect("get to work"), None);
+    }
 }
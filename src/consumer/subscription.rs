@@ -0,0 +1,211 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::messaging::{Get, GetError, MessageData};
+
+/// A message fetched ahead of the consumer, along with its position as reported by the `Get`
+pub(crate) type Prefetched = Result<(u64, MessageData), GetError>;
+
+/// Fetches messages on its own thread into a bounded prefetch queue, so fetching overlaps with
+/// the consumer handling the messages already fetched. The thread waits while the queue is full.
+///
+/// In Eventide this is the subscription actor together with the consumer actor's prefetch queue
+pub(crate) struct Subscription<G: Get> {
+    receiver: Receiver<Prefetched>,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<G>>,
+}
+
+impl<G: Get + Send + 'static> Subscription<G> {
+    pub(crate) fn start(get: G, position: u64, queue_size: usize, poll_interval: Duration) -> Self {
+        let (sender, receiver) = sync_channel(queue_size);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+
+        let handle = std::thread::spawn(move || {
+            let mut get = get;
+            let mut position = position;
+
+            while !thread_stopped.load(Ordering::Relaxed) {
+                match get.get(position as i64) {
+                    Ok(messages) if messages.is_empty() => std::thread::sleep(poll_interval),
+                    Ok(messages) => {
+                        for message_data in messages {
+                            let message_position = get.message_position(&message_data);
+                            position = message_position + 1;
+
+                            // Only fails once the consumer has dropped the queue
+                            if sender.send(Ok((message_position, message_data))).is_err() {
+                                return get;
+                            }
+                        }
+                    }
+                    // The consumer records the failure, and the same position is fetched again
+                    Err(error @ GetError::ConnectionLost(_)) => {
+                        if sender.send(Err(error)).is_err() {
+                            return get;
+                        }
+                        std::thread::sleep(poll_interval);
+                    }
+                    Err(error) => {
+                        let _ = sender.send(Err(error));
+                        return get;
+                    }
+                }
+            }
+
+            get
+        });
+
+        Self {
+            receiver,
+            stopped,
+            handle: Some(handle),
+        }
+    }
+
+    /// The next message in the prefetch queue, if one is ready
+    pub(crate) fn next(&self) -> Option<Prefetched> {
+        match self.receiver.try_recv() {
+            Ok(prefetched) => Some(prefetched),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Stops fetching, discarding anything still in the queue, and hands back the `Get`
+    pub(crate) fn stop(mut self) -> G {
+        let handle = self.handle.take().expect("subscription to be running");
+        // Also drops the queue, releasing the thread if it is waiting for room
+        drop(self);
+
+        handle.join().expect("subscription thread to join")
+    }
+}
+
+impl<G: Get> Drop for Subscription<G> {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{channel, Sender};
+    use std::time::Instant;
+
+    use super::*;
+    use crate::{
+        controls,
+        messaging::{GetTelemetry, SubstituteGetter},
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Reports every position it is asked for, so a test can wait on the subscription thread
+    struct RecordingGet {
+        get: SubstituteGetter,
+        positions: Sender<i64>,
+    }
+
+    impl Get for RecordingGet {
+        fn get(&mut self, position: i64) -> Result<Vec<MessageData>, GetError> {
+            let _ = self.positions.send(position);
+            self.get.get(position)
+        }
+    }
+
+    impl GetTelemetry for RecordingGet {
+        fn record_get(&mut self) {
+            self.get.record_get()
+        }
+
+        fn record_got_messages(&mut self, messages: &[MessageData]) {
+            self.get.record_got_messages(messages)
+        }
+    }
+
+    fn subscription(
+        messages: &[MessageData],
+        queue_size: usize,
+    ) -> (Subscription<RecordingGet>, Receiver<i64>) {
+        let mut get = SubstituteGetter::new("my_category");
+        get.queue_messages(messages);
+        let position = get.beginning_position();
+        let (positions, requested_positions) = channel();
+
+        let subscription = Subscription::start(
+            RecordingGet { get, positions },
+            position,
+            queue_size,
+            Duration::from_millis(1),
+        );
+
+        (subscription, requested_positions)
+    }
+
+    fn receive(subscription: &Subscription<RecordingGet>) -> (u64, MessageData) {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            if let Some(prefetched) = subscription.next() {
+                return prefetched.expect("get to work");
+            }
+            assert!(
+                Instant::now() < deadline,
+                "no message was prefetched in time"
+            );
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn should_prefetch_messages_in_order_with_their_positions() {
+        let messages = controls::messages::example();
+        let (subscription, _) = subscription(&messages, messages.len());
+
+        let received: Vec<_> = messages.iter().map(|_| receive(&subscription)).collect();
+
+        let expected: Vec<_> = messages
+            .iter()
+            .map(|message| (message.global_position, message.clone()))
+            .collect();
+        assert_eq!(received, expected);
+        subscription.stop();
+    }
+
+    #[test]
+    fn should_not_fetch_more_while_the_queue_is_full() {
+        let start = controls::messages::beginning_global_position();
+        let messages: Vec<_> = (0..3)
+            .map(|index| controls::messages::example_message_data(index, start + index))
+            .collect();
+        let (subscription, _) = subscription(&messages, 1);
+
+        // The third message can't be queued until the second is taken, so the thread can't
+        // have fetched again
+        receive(&subscription);
+        let get = subscription.stop();
+
+        let only_the_first_batch = 1;
+        assert_eq!(get.get.get_count(), only_the_first_batch);
+    }
+
+    #[test]
+    fn should_keep_polling_from_the_next_position() {
+        let messages = controls::messages::example();
+        let (subscription, requested_positions) = subscription(&messages, messages.len());
+
+        messages.iter().for_each(|_| {
+            receive(&subscription);
+        });
+
+        let first = requested_positions.recv_timeout(TIMEOUT).expect("a get");
+        let next = requested_positions.recv_timeout(TIMEOUT).expect("a get");
+        let next_position = messages.last().expect("messages").global_position + 1;
+        assert_eq!(first, messages[0].global_position as i64);
+        assert_eq!(next, next_position as i64);
+        subscription.stop();
+    }
+}
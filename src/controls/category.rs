@@ -1,5 +1,8 @@
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use uuid::Uuid;
+
+use crate::messaging::StreamName;
 
 pub fn unique_category() -> String {
     thread_rng()
@@ -8,3 +11,14 @@ pub fn unique_category() -> String {
         .map(char::from)
         .collect()
 }
+
+pub fn unique_stream_name(category: &str) -> String {
+    stream_name(category, &Uuid::new_v4())
+}
+
+pub fn stream_name(category: &str, id: &Uuid) -> String {
+    StreamName::parse(category)
+        .and_then(|stream_name| stream_name.with_id(id.to_hyphenated().to_string()))
+        .expect("category to be a valid stream name category")
+        .to_string()
+}
@@ -65,3 +65,41 @@ impl FailingHandler {
         *count
     }
 }
+
+/// Fails the first `failures` times it is called, then handles messages
+#[derive(Debug, Clone)]
+pub struct FlakyHandler {
+    failures: Arc<Mutex<u64>>,
+    count: Arc<Mutex<u64>>,
+}
+
+impl Handler for FlakyHandler {
+    fn handle(&mut self, _message: MessageData) -> Result<(), HandleError> {
+        let mut count = self.count.lock().expect("mutex to not be poisoned");
+        *count += 1;
+
+        let mut failures = self.failures.lock().expect("mutex to not be poisoned");
+        if *failures > 0 {
+            *failures -= 1;
+            return Err(Box::new(FailingHandlerError::Forced).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl FlakyHandler {
+    pub fn build(failures: u64) -> Self {
+        Self {
+            failures: Arc::new(Mutex::new(failures)),
+            count: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Every call, failed or not
+    pub fn message_count(&self) -> u64 {
+        let count = self.count.lock().expect("mutex to not be poisoned");
+
+        *count
+    }
+}
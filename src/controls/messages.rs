@@ -1,19 +1,52 @@
-use crate::messaging::MessageData;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::messaging::{Message, MessageData};
 
 pub mod postgres;
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Example {
+    pub some_attribute: String,
+}
+
+impl Message for Example {
+    const TYPE_NAME: &'static str = "Example";
+}
+
+pub fn example_message() -> Example {
+    Example {
+        some_attribute: "some value".to_string(),
+    }
+}
+
 pub fn beginning_global_position() -> u64 {
     1
 }
 
+pub fn example_stream_name() -> String {
+    "example-00000000-0000-4000-8000-000000000000".to_string()
+}
+
 pub fn example() -> Vec<MessageData> {
     let starting_position = beginning_global_position();
     vec![
-        MessageData {
-            global_position: starting_position,
-        },
-        MessageData {
-            global_position: starting_position + 1,
-        },
+        example_message_data(0, starting_position),
+        example_message_data(1, starting_position + 1),
     ]
 }
+
+pub fn example_message_data(position: u64, global_position: u64) -> MessageData {
+    MessageData {
+        id: format!("00000000-0000-4000-8000-{:012}", global_position),
+        stream_name: example_stream_name(),
+        message_type: "Example".to_string(),
+        position,
+        global_position,
+        data: json!({ "some_attribute": "some value" }),
+        metadata: None,
+        time: SystemTime::UNIX_EPOCH,
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use uuid::Uuid;
 
-use crate::session::Session;
+use crate::{messaging::Metadata, session::Session};
 
 pub fn write_random_message_to_random_category() -> String {
     let mut session = Session::build().expect("session to build");
@@ -10,7 +10,7 @@ pub fn write_random_message_to_random_category() -> String {
     let category = crate::controls::category::unique_category();
 
     let id = Uuid::new_v4();
-    let stream_name = format!("{}-{}", category, id.to_hyphenated().to_string());
+    let stream_name = crate::controls::category::stream_name(&category, &id);
     let message_type = "Random";
     let empty_object: HashMap<String, String> = HashMap::new();
     let data = serde_json::to_value(&empty_object).expect("to_string_to_work");
@@ -38,7 +38,7 @@ pub fn write_random_message_to_category(category: &str) {
     let mut session = Session::build().expect("session to build");
 
     let id = Uuid::new_v4();
-    let stream_name = format!("{}-{}", category, id.to_hyphenated().to_string());
+    let stream_name = crate::controls::category::stream_name(category, &id);
     let message_type = "Random";
     let empty_object: HashMap<String, String> = HashMap::new();
     let data = serde_json::to_value(&empty_object).expect("to_string_to_work");
@@ -89,13 +89,14 @@ pub fn write_random_message_with_correlation_to_category(category: &str, correla
     let mut session = Session::build().expect("session to build");
 
     let id = Uuid::new_v4();
-    let stream_name = format!("{}-{}", category, id.to_hyphenated().to_string());
+    let stream_name = crate::controls::category::stream_name(category, &id);
     let message_type = "Random";
     let empty_object: HashMap<String, String> = HashMap::new();
     let data = serde_json::to_value(&empty_object).expect("to_string_to_work");
-    // correlationStreamName
-    let mut correlation_meta_data = HashMap::new();
-    correlation_meta_data.insert("correlationStreamName", correlation.to_string());
+    let correlation_meta_data = Metadata {
+        correlation_stream_name: Some(correlation.to_string()),
+        ..Metadata::new()
+    };
     let value =
         serde_json::to_value(&correlation_meta_data).expect("metadata to convert to json value");
     let meta_data: Option<serde_json::Value> = Some(value);
@@ -122,7 +123,7 @@ pub fn write_one_random_message_with_data_to_category(data_map: HashMap<&str, &s
     let category = crate::controls::category::unique_category();
 
     let id = Uuid::new_v4();
-    let stream_name = format!("{}-{}", category, id.to_hyphenated().to_string());
+    let stream_name = crate::controls::category::stream_name(&category, &id);
     let message_type = "Random";
     let data = serde_json::to_value(&data_map).expect("to_string_to_work");
     let meta_data: Option<serde_json::Value> = None;
@@ -154,7 +155,7 @@ pub fn write_one_random_message_for_consumer_and_one_not_to_random_category(
     let category = crate::controls::category::unique_category();
 
     let id = stream_id_for_consumer_in_group(consumer_group_member, consumer_group_size);
-    let stream_name = format!("{}-{}", category, id.to_hyphenated().to_string());
+    let stream_name = crate::controls::category::stream_name(&category, &id);
     let message_type = "Random";
     let empty_object: HashMap<String, String> = HashMap::new();
     let data = serde_json::to_value(&empty_object).expect("to_string_to_work");
@@ -176,7 +177,7 @@ pub fn write_one_random_message_for_consumer_and_one_not_to_random_category(
         .expect("random write to work");
 
     let id = stream_id_not_for_consumer_in_group(consumer_group_member, consumer_group_size);
-    let stream_name = format!("{}-{}", category, id.to_hyphenated().to_string());
+    let stream_name = crate::controls::category::stream_name(&category, &id);
     let message_type = "Random";
     let empty_object: HashMap<String, String> = HashMap::new();
     let data = serde_json::to_value(&empty_object).expect("to_string_to_work");
@@ -272,15 +273,6 @@ pub fn stream_id_not_for_consumer_in_group(
     possible_uuid
 }
 
-pub fn enable_condition_for_session(session: &mut Session) {
-    session
-        .query(
-            "SELECT set_config('message_store.sql_condition', 'on', false);",
-            &[],
-        )
-        .expect("the mod query to run");
-}
-
 pub fn current_max_global_position() -> i64 {
     let mut session = Session::build().expect("session to build");
 
@@ -291,7 +283,7 @@ pub fn current_max_global_position() -> i64 {
         )
         .expect("the mod query to run");
 
-    rows.get(0)
+    rows.first()
         .and_then(|row| row.get("max_global_position"))
         .unwrap_or(-1i64)
 }
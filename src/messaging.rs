@@ -1,15 +1,40 @@
 use std::error::Error as StdError;
+use std::time::SystemTime;
 
+use serde_json::Value;
 use thiserror::Error;
 
+pub mod condition;
+pub mod dispatch;
 pub mod get;
+pub mod message;
+pub mod metadata;
 pub mod postgres;
+pub mod read;
+pub mod stream_name;
+pub mod write;
 
+pub use condition::*;
+pub use dispatch::*;
 pub use get::*;
+pub use message::*;
+pub use metadata::*;
+pub use read::*;
+pub use stream_name::*;
+pub use write::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MessageData {
+    pub id: String,
+    pub stream_name: String,
+    /// The `type` column of the message
+    pub message_type: String,
+    /// Position of the message in its stream
+    pub position: u64,
     pub global_position: u64,
+    pub data: Value,
+    pub metadata: Option<Value>,
+    pub time: SystemTime,
 }
 
 #[derive(Error, Debug)]
@@ -18,6 +43,14 @@ pub enum HandleError {
     HandlerError(#[from] Box<dyn StdError + Send>),
     #[error("Unable to get messages {0}")]
     GetError(#[from] GetError),
+    #[error("Unable to convert message {0}")]
+    MessageError(#[from] MessageError),
+    #[error("Unable to write message {0}")]
+    WriteError(#[from] WriteError),
+    #[error("Unable to store position {0}")]
+    PositionStoreError(#[from] crate::consumer::position_store::PositionStoreError),
+    #[error("{0}")]
+    InvalidSettings(#[from] crate::settings::InvalidSettings),
     #[error("Missing Handler")]
     MissingHandler,
 }
@@ -0,0 +1,504 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DATA_COLUMN: &str = "data";
+const METADATA_COLUMN: &str = "metadata";
+const TYPE_COLUMN: &str = "type";
+const TIME_COLUMN: &str = "time";
+const PATH_SEPARATOR: char = '.';
+
+/// ISO 8601 timestamps PostgreSQL can cast, so a field is only cast once it is known to be one:
+/// a date that exists, leap days included, then optionally a time and a UTC offset
+const TIMESTAMP_PATTERN: &str = concat!(
+    // Year 0000 doesn't exist
+    r"^(?!0000)(?:",
+    // Months with 31 days, months with 30 and February without leap days
+    r"\d{4}-(?:(?:0[13578]|1[02])-(?:0[1-9]|[12]\d|3[01])|(?:0[469]|11)-(?:0[1-9]|[12]\d|30)|02-(?:0[1-9]|1\d|2[0-8]))",
+    // Leap days of years divisible by 4, except centuries not divisible by 400
+    r"|(?:\d{2}(?:0[48]|[2468][048]|[13579][26])|(?:[02468][048]|[13579][26])00)-02-29)",
+    // Time, with optional seconds and fraction, then an optional offset of up to 15:59
+    r"(?:[T ](?:[01]\d|2[0-3]):[0-5]\d(?::[0-5]\d(?:\.\d{1,6})?)?(?:Z|[+-](?:0\d|1[0-5])(?::?[0-5]\d)?)?)?$",
+);
+
+/// A `Settings::condition` built from typed comparisons rather than a raw SQL fragment, e.g.
+/// `Condition::data("amount").gt(100).and(Condition::message_type().eq("Deposited"))`.
+/// Every value is rendered as a quoted literal so it is safe to build from input.
+///
+/// Comparing a data or metadata field to a number or boolean only matches messages where the
+/// field holds a JSON number or boolean, and comparing it to a time only matches strings holding
+/// a valid ISO 8601 timestamp, e.g. `2024-01-02` or `2024-01-02T03:04:05Z`, so other values in
+/// the category, such as `2024-13-01`, don't fail the whole query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Comparison {
+        field: FieldKind,
+        operator: Operator,
+        value: ConditionValue,
+    },
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldKind {
+    /// `.` separated path into the message data
+    Data(Vec<String>),
+    /// `.` separated path into the message metadata
+    Metadata(Vec<String>),
+    Type,
+    Time,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionValue {
+    Text(String),
+    Integer(i64),
+    Unsigned(u64),
+    Float(f64),
+    Boolean(bool),
+    Time(SystemTime),
+}
+
+/// A field of a message that can be compared to a `V`
+#[derive(Debug, Clone)]
+pub struct Field<V> {
+    kind: FieldKind,
+    value: PhantomData<V>,
+}
+
+impl Condition {
+    /// A field of the message data, nested fields are separated by `.`, e.g. `account.id`
+    pub fn data(path: &str) -> Field<ConditionValue> {
+        Field::new(FieldKind::Data(split_path(path)))
+    }
+
+    /// A field of the message metadata, nested fields are separated by `.`
+    pub fn metadata(path: &str) -> Field<ConditionValue> {
+        Field::new(FieldKind::Metadata(split_path(path)))
+    }
+
+    pub fn message_type() -> Field<String> {
+        Field::new(FieldKind::Type)
+    }
+
+    pub fn time() -> Field<SystemTime> {
+        Field::new(FieldKind::Time)
+    }
+
+    pub fn and(self, other: Condition) -> Condition {
+        Condition::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Condition) -> Condition {
+        Condition::Or(Box::new(self), Box::new(other))
+    }
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.split(PATH_SEPARATOR).map(str::to_string).collect()
+}
+
+impl<V: Into<ConditionValue>> Field<V> {
+    fn new(kind: FieldKind) -> Self {
+        Self {
+            kind,
+            value: PhantomData,
+        }
+    }
+
+    fn compare(self, operator: Operator, value: impl Into<V>) -> Condition {
+        Condition::Comparison {
+            field: self.kind,
+            operator,
+            value: value.into().into(),
+        }
+    }
+
+    pub fn eq(self, value: impl Into<V>) -> Condition {
+        self.compare(Operator::Eq, value)
+    }
+
+    pub fn ne(self, value: impl Into<V>) -> Condition {
+        self.compare(Operator::Ne, value)
+    }
+
+    pub fn gt(self, value: impl Into<V>) -> Condition {
+        self.compare(Operator::Gt, value)
+    }
+
+    pub fn ge(self, value: impl Into<V>) -> Condition {
+        self.compare(Operator::Ge, value)
+    }
+
+    pub fn lt(self, value: impl Into<V>) -> Condition {
+        self.compare(Operator::Lt, value)
+    }
+
+    pub fn le(self, value: impl Into<V>) -> Condition {
+        self.compare(Operator::Le, value)
+    }
+}
+
+impl From<&str> for ConditionValue {
+    fn from(value: &str) -> Self {
+        ConditionValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for ConditionValue {
+    fn from(value: String) -> Self {
+        ConditionValue::Text(value)
+    }
+}
+
+impl From<i64> for ConditionValue {
+    fn from(value: i64) -> Self {
+        ConditionValue::Integer(value)
+    }
+}
+
+impl From<i32> for ConditionValue {
+    fn from(value: i32) -> Self {
+        ConditionValue::Integer(value.into())
+    }
+}
+
+impl From<u32> for ConditionValue {
+    fn from(value: u32) -> Self {
+        ConditionValue::Integer(value.into())
+    }
+}
+
+impl From<u64> for ConditionValue {
+    fn from(value: u64) -> Self {
+        ConditionValue::Unsigned(value)
+    }
+}
+
+impl From<f64> for ConditionValue {
+    fn from(value: f64) -> Self {
+        ConditionValue::Float(value)
+    }
+}
+
+impl From<bool> for ConditionValue {
+    fn from(value: bool) -> Self {
+        ConditionValue::Boolean(value)
+    }
+}
+
+impl From<SystemTime> for ConditionValue {
+    fn from(value: SystemTime) -> Self {
+        ConditionValue::Time(value)
+    }
+}
+
+/// A SQL string literal, doubling any `'` so the value cannot end the literal early
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Message DB stores `time` as a UTC timestamp without a time zone
+fn timestamp(time: &SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(error) => -error.duration().as_secs_f64(),
+    };
+
+    format!("(to_timestamp({:.6}) AT TIME ZONE 'UTC')", seconds)
+}
+
+impl ConditionValue {
+    fn cast(&self) -> Option<&'static str> {
+        match self {
+            ConditionValue::Text(_) => None,
+            ConditionValue::Integer(_) | ConditionValue::Unsigned(_) | ConditionValue::Float(_) => {
+                Some("numeric")
+            }
+            ConditionValue::Boolean(_) => Some("boolean"),
+            ConditionValue::Time(_) => Some("timestamp"),
+        }
+    }
+
+    /// Whether the field at `json` (the JSON value) and `text` (its text) can be cast for the
+    /// comparison
+    fn castable(&self, json: &str, text: &str) -> Option<String> {
+        match self {
+            ConditionValue::Text(_) => None,
+            ConditionValue::Integer(_) | ConditionValue::Unsigned(_) | ConditionValue::Float(_) => {
+                Some(format!("jsonb_typeof({}) = 'number'", json))
+            }
+            ConditionValue::Boolean(_) => Some(format!("jsonb_typeof({}) = 'boolean'", json)),
+            ConditionValue::Time(_) => Some(format!("{} ~ '{}'", text, TIMESTAMP_PATTERN)),
+        }
+    }
+}
+
+impl fmt::Display for ConditionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionValue::Text(value) => write!(f, "{}", quote(value)),
+            ConditionValue::Integer(value) => write!(f, "{}", value),
+            ConditionValue::Unsigned(value) => write!(f, "{}", value),
+            ConditionValue::Float(value) if value.is_finite() => write!(f, "{}", value),
+            // Non finite numbers match nothing, as any comparison with NULL does
+            ConditionValue::Float(_) => write!(f, "NULL"),
+            ConditionValue::Boolean(value) => write!(f, "{}", value),
+            ConditionValue::Time(time) => write!(f, "{}", timestamp(time)),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match self {
+            Operator::Eq => "=",
+            Operator::Ne => "<>",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+        };
+
+        write!(f, "{}", operator)
+    }
+}
+
+/// `column->'a'->'b'` for the JSON value of the field at the path, or `column->'a'->>'b'` for
+/// its text
+fn json_field(column: &str, path: &[String], text: bool) -> String {
+    let mut field = column.to_string();
+
+    for (index, key) in path.iter().enumerate() {
+        let arrow = if text && index + 1 == path.len() {
+            "->>"
+        } else {
+            "->"
+        };
+        field.push_str(arrow);
+        field.push_str(&quote(key));
+    }
+
+    field
+}
+
+/// Casts are guarded with a `CASE`, which is `NULL` and so matches nothing for values that can't
+/// be cast
+fn render_field(field: &FieldKind, value: &ConditionValue) -> String {
+    let (column, path) = match field {
+        FieldKind::Data(path) => (DATA_COLUMN, path),
+        FieldKind::Metadata(path) => (METADATA_COLUMN, path),
+        FieldKind::Type => return TYPE_COLUMN.to_string(),
+        FieldKind::Time => return TIME_COLUMN.to_string(),
+    };
+    let text = json_field(column, path, true);
+
+    match (
+        value.cast(),
+        value.castable(&json_field(column, path, false), &text),
+    ) {
+        (Some(cast), Some(castable)) => {
+            format!("(CASE WHEN {} THEN ({})::{} END)", castable, text, cast)
+        }
+        _ => text,
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Comparison {
+                field,
+                operator,
+                value,
+            } => write!(f, "{} {} {}", render_field(field, value), operator, value),
+            Condition::And(left, right) => write!(f, "({} AND {})", left, right),
+            Condition::Or(left, right) => write!(f, "({} OR {})", left, right),
+        }
+    }
+}
+
+impl From<Condition> for String {
+    fn from(condition: Condition) -> Self {
+        condition.to_string()
+    }
+}
+
+impl From<&Condition> for String {
+    fn from(condition: &Condition) -> Self {
+        condition.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn should_compare_data_fields_as_text() {
+        let condition = Condition::data("name").eq("some name");
+
+        assert_eq!(condition.to_string(), "data->>'name' = 'some name'");
+    }
+
+    #[test]
+    fn should_cast_data_fields_compared_to_numbers_and_booleans() {
+        assert_eq!(
+            Condition::data("amount").gt(100).to_string(),
+            "(CASE WHEN jsonb_typeof(data->'amount') = 'number' THEN (data->>'amount')::numeric END) > 100"
+        );
+        assert_eq!(
+            Condition::data("amount").le(10.5).to_string(),
+            "(CASE WHEN jsonb_typeof(data->'amount') = 'number' THEN (data->>'amount')::numeric END) <= 10.5"
+        );
+        assert_eq!(
+            Condition::data("active").ne(true).to_string(),
+            "(CASE WHEN jsonb_typeof(data->'active') = 'boolean' THEN (data->>'active')::boolean END) <> true"
+        );
+    }
+
+    #[test]
+    fn should_only_cast_data_fields_holding_a_timestamp() {
+        let time = UNIX_EPOCH + Duration::from_secs(1);
+
+        assert_eq!(
+            Condition::data("at").lt(time).to_string(),
+            format!(
+                "(CASE WHEN data->>'at' ~ '{}' THEN (data->>'at')::timestamp END) < (to_timestamp(1.000000) AT TIME ZONE 'UTC')",
+                TIMESTAMP_PATTERN
+            )
+        );
+    }
+
+    #[test]
+    fn should_compare_unsigned_numbers() {
+        let condition = Condition::metadata("position").ge(u64::MAX);
+
+        assert_eq!(
+            condition.to_string(),
+            format!(
+                "(CASE WHEN jsonb_typeof(metadata->'position') = 'number' THEN (metadata->>'position')::numeric END) >= {}",
+                u64::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn should_follow_nested_paths() {
+        let condition = Condition::metadata("properties.tenant").eq("acme");
+
+        assert_eq!(
+            condition.to_string(),
+            "metadata->'properties'->>'tenant' = 'acme'"
+        );
+    }
+
+    #[test]
+    fn should_compare_the_message_type() {
+        let condition = Condition::message_type().eq("Deposited");
+
+        assert_eq!(condition.to_string(), "type = 'Deposited'");
+    }
+
+    #[test]
+    fn should_compare_the_time_in_utc() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_500);
+        let condition = Condition::time().ge(time);
+
+        assert_eq!(
+            condition.to_string(),
+            "time >= (to_timestamp(1.500000) AT TIME ZONE 'UTC')"
+        );
+    }
+
+    #[test]
+    fn should_quote_values_and_paths() {
+        let condition = Condition::data("it's").eq("'; DROP TABLE messages; --");
+
+        assert_eq!(
+            condition.to_string(),
+            "data->>'it''s' = '''; DROP TABLE messages; --'"
+        );
+    }
+
+    #[test]
+    fn should_group_combined_conditions() {
+        let condition = Condition::message_type().eq("Deposited").and(
+            Condition::data("amount")
+                .gt(100)
+                .or(Condition::data("priority").eq(true)),
+        );
+
+        assert_eq!(
+            condition.to_string(),
+            "(type = 'Deposited' AND ((CASE WHEN jsonb_typeof(data->'amount') = 'number' THEN (data->>'amount')::numeric END) > 100 OR (CASE WHEN jsonb_typeof(data->'priority') = 'boolean' THEN (data->>'priority')::boolean END) = true))"
+        );
+    }
+
+    #[test]
+    fn should_render_to_the_settings_condition() {
+        let mut settings = crate::settings::Settings::new();
+
+        settings.condition = Some(Condition::message_type().eq("Deposited").into());
+
+        assert_eq!(settings.condition.as_deref(), Some("type = 'Deposited'"));
+    }
+}
+
+#[cfg(all(test, feature = "integration_tests"))]
+mod integration_tests {
+    use super::*;
+    use crate::session::Session;
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn should_only_cast_valid_timestamps() {
+        init();
+
+        // Arrange
+        let condition = Condition::data("at").gt(UNIX_EPOCH);
+        let values = [
+            "2024-01-02",
+            "2024-02-29T03:04:05.123456Z",
+            "2024-13-01",
+            "2023-02-29",
+            "2024-04-31",
+            "2024-01-01garbage",
+            "2024-01-02T24:00:00",
+            "not a time",
+        ];
+        let query = format!(
+            "SELECT data->>'at' AS at FROM jsonb_array_elements($1::jsonb) AS data WHERE {};",
+            condition
+        );
+        let data = serde_json::Value::from(
+            values
+                .iter()
+                .map(|value| serde_json::json!({ "at": value }))
+                .collect::<Vec<_>>(),
+        );
+        let mut session = Session::build().expect("session to build");
+
+        // Act
+        let rows = session.query(&query, &[&data]).expect("query to work");
+
+        // Assert
+        let matched: Vec<String> = rows.iter().map(|row| row.get("at")).collect();
+        assert_eq!(matched, vec!["2024-01-02", "2024-02-29T03:04:05.123456Z"]);
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use crate::messaging::{HandleError, Handler, Message, MessageData};
+
+type Callback = Box<dyn FnMut(&MessageData) -> Result<(), HandleError> + Send>;
+
+/// A [`Handler`] that routes each message to the callback registered for its type.
+/// Messages with a type that has no callback are skipped.
+pub struct Dispatcher {
+    callbacks: HashMap<&'static str, Callback>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self {
+            callbacks: HashMap::new(),
+        }
+    }
+
+    pub fn build() -> Self {
+        Self::new()
+    }
+
+    /// Registers the callback for `M`, replacing any previously registered for the same type
+    pub fn on<M: Message + 'static>(
+        mut self,
+        mut callback: impl FnMut(M, &MessageData) -> Result<(), HandleError> + Send + 'static,
+    ) -> Self {
+        self.callbacks.insert(
+            M::TYPE_NAME,
+            Box::new(move |message_data| {
+                let message = M::from_message_data(message_data)?;
+                callback(message, message_data)
+            }),
+        );
+        self
+    }
+
+    pub fn handles(&self, message_type: &str) -> bool {
+        self.callbacks.contains_key(message_type)
+    }
+}
+
+impl Default for Dispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Dispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut message_types: Vec<_> = self.callbacks.keys().collect();
+        message_types.sort();
+
+        f.debug_struct("Dispatcher")
+            .field("message_types", &message_types)
+            .finish()
+    }
+}
+
+impl Handler for Dispatcher {
+    fn handle(&mut self, message: MessageData) -> Result<(), HandleError> {
+        match self.callbacks.get_mut(message.message_type.as_str()) {
+            Some(callback) => callback(&message),
+            None => {
+                log::trace!("No handler for message type: {}", message.message_type);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::controls::{self, messages::Example};
+
+    #[test]
+    fn should_call_the_callback_registered_for_the_message_type() {
+        let handled = Arc::new(Mutex::new(vec![]));
+        let handled_by_callback = handled.clone();
+
+        let mut dispatcher = Dispatcher::build().on::<Example>(move |message, message_data| {
+            handled_by_callback
+                .lock()
+                .expect("mutex to not be poisoned")
+                .push((message, message_data.global_position));
+            Ok(())
+        });
+
+        let message_data = controls::messages::example_message_data(0, 1);
+        dispatcher.handle(message_data).expect("handle to work");
+
+        let handled = handled.lock().expect("mutex to not be poisoned");
+        assert_eq!(*handled, vec![(controls::messages::example_message(), 1)]);
+    }
+
+    #[test]
+    fn should_skip_messages_with_no_registered_callback() {
+        let count = Arc::new(Mutex::new(0));
+        let callback_count = count.clone();
+
+        let mut dispatcher = Dispatcher::build().on::<Example>(move |_, _| {
+            *callback_count.lock().expect("mutex to not be poisoned") += 1;
+            Ok(())
+        });
+
+        let mut message_data = controls::messages::example_message_data(0, 1);
+        message_data.message_type = "Unhandled".to_string();
+
+        dispatcher.handle(message_data).expect("handle to work");
+
+        assert_eq!(*count.lock().expect("mutex to not be poisoned"), 0);
+        assert!(dispatcher.handles(Example::TYPE_NAME));
+        assert!(!dispatcher.handles("Unhandled"));
+    }
+
+    #[test]
+    fn should_return_conversion_errors_as_handle_errors() {
+        let mut dispatcher = Dispatcher::build().on::<Example>(|_, _| Ok(()));
+
+        let mut message_data = controls::messages::example_message_data(0, 1);
+        message_data.data = serde_json::json!({ "some_attribute": 1 });
+
+        let result = dispatcher.handle(message_data);
+
+        assert!(matches!(result, Err(HandleError::MessageError(_))));
+    }
+}
@@ -6,14 +6,32 @@ use std::error::Error as StdError;
 
 use crate::messaging::MessageData;
 
+pub const BEGINNING_GLOBAL_POSITION: u64 = 1;
+pub const BEGINNING_STREAM_POSITION: u64 = 0;
+
 pub trait Get: GetTelemetry {
     fn get(&mut self, position: i64) -> Result<Vec<MessageData>, GetError>;
+
+    /// Where reading starts when no position has been stored
+    fn beginning_position(&self) -> u64 {
+        BEGINNING_GLOBAL_POSITION
+    }
+
+    /// The position of a message that `get` is called with (plus one) to read past it
+    fn message_position(&self, message_data: &MessageData) -> u64 {
+        message_data.global_position
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum GetError {
     #[error("An error getting data occurred: {0}")]
     DataError(#[from] Box<dyn StdError + Send + Sync>),
+    #[error("Message data or metadata is not valid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    /// The message store couldn't be reached, even after reconnecting. Fetching again later may work.
+    #[error("Lost the connection to the message store: {0}")]
+    ConnectionLost(Box<dyn StdError + Send + Sync>),
 }
 
 pub trait GetTelemetry {
@@ -27,6 +45,7 @@ pub struct SubstituteGetter {
     category: String,
     last_position: Option<i64>,
     messages: Vec<MessageData>,
+    lost_connections: u64,
     telemetry: HashMap<String, Value>,
 }
 
@@ -36,6 +55,7 @@ impl SubstituteGetter {
             category: category.to_string(),
             last_position: None,
             messages: vec![],
+            lost_connections: 0,
             telemetry: HashMap::new(),
         }
     }
@@ -44,29 +64,22 @@ impl SubstituteGetter {
         self.messages.extend_from_slice(messages)
     }
 
+    /// Makes the next `times` gets fail as though the connection to the message store was lost
+    pub fn lose_connection(&mut self, times: u64) {
+        self.lost_connections = times;
+    }
+
     pub fn get_count(&self) -> u64 {
         self.telemetry
             .get("get_count")
-            .map(|value| {
-                if let Some(count) = value.as_u64() {
-                    count
-                } else {
-                    0
-                }
-            })
+            .map(|value| value.as_u64().unwrap_or_default())
             .unwrap_or(0)
     }
 
     pub fn get_messages_count(&self) -> u64 {
         self.telemetry
             .get("get_messages_count")
-            .map(|value| {
-                if let Some(count) = value.as_u64() {
-                    count
-                } else {
-                    0
-                }
-            })
+            .map(|value| value.as_u64().unwrap_or_default())
             .unwrap_or(0)
     }
 
@@ -79,11 +92,17 @@ impl Get for SubstituteGetter {
     fn get(&mut self, position: i64) -> Result<Vec<MessageData>, GetError> {
         self.last_position = Some(position);
         self.record_get();
-        if self.messages.len() > 0 {
+        if self.lost_connections > 0 {
+            self.lost_connections -= 1;
+            return Err(GetError::ConnectionLost(
+                "Substitute getter set to lose its connection".into(),
+            ));
+        }
+        if !self.messages.is_empty() {
             let messages = self.messages.clone();
             let messages_index = position - 1;
             let limited_messages = &messages[messages_index as usize..];
-            self.record_got_messages(&limited_messages);
+            self.record_got_messages(limited_messages);
 
             Ok(limited_messages.to_vec())
         } else {
@@ -143,7 +162,7 @@ mod tests {
         let mut get = SubstituteGetter::new("my_category");
         let beginning_position = messages::beginning_global_position() as i64;
         let returned_messages = get.get(beginning_position).expect("get to work");
-        assert!(returned_messages.len() == 0);
+        assert!(returned_messages.is_empty());
     }
 
     #[test]
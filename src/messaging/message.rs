@@ -0,0 +1,128 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::messaging::{MessageData, Metadata};
+
+/// A typed message that can be read from, and written to, the message store
+pub trait Message: Serialize + DeserializeOwned {
+    /// Stored in the `type` column of the message
+    const TYPE_NAME: &'static str;
+
+    fn from_message_data(message_data: &MessageData) -> Result<Self, MessageError> {
+        if message_data.message_type != Self::TYPE_NAME {
+            return Err(MessageError::TypeMismatch {
+                expected: Self::TYPE_NAME,
+                actual: message_data.message_type.clone(),
+            });
+        }
+
+        Ok(serde_json::from_value(message_data.data.clone())?)
+    }
+
+    fn to_write_message_data(&self) -> Result<WriteMessageData, MessageError> {
+        Ok(WriteMessageData {
+            id: Uuid::new_v4().to_hyphenated().to_string(),
+            message_type: Self::TYPE_NAME.to_string(),
+            data: serde_json::to_value(self)?,
+            metadata: None,
+        })
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum MessageError {
+    #[error("Expected message type {expected} but got {actual}")]
+    TypeMismatch {
+        expected: &'static str,
+        actual: String,
+    },
+    #[error("Unable to convert message data: {0}")]
+    DataError(#[from] serde_json::Error),
+}
+
+/// The parts of a message that are supplied when writing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteMessageData {
+    pub id: String,
+    pub message_type: String,
+    pub data: Value,
+    pub metadata: Option<Metadata>,
+}
+
+impl WriteMessageData {
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls;
+
+    #[test]
+    fn should_convert_message_data_into_message() {
+        let message_data = controls::messages::example_message_data(0, 1);
+
+        let message = controls::messages::Example::from_message_data(&message_data)
+            .expect("conversion to work");
+
+        assert_eq!(message, controls::messages::example_message());
+    }
+
+    #[test]
+    fn should_error_when_message_type_does_not_match() {
+        let mut message_data = controls::messages::example_message_data(0, 1);
+        message_data.message_type = "SomethingElse".to_string();
+
+        let result = controls::messages::Example::from_message_data(&message_data);
+
+        assert!(matches!(
+            result,
+            Err(MessageError::TypeMismatch { expected: "Example", ref actual }) if actual == "SomethingElse"
+        ));
+    }
+
+    #[test]
+    fn should_error_when_data_does_not_fit_message() {
+        let mut message_data = controls::messages::example_message_data(0, 1);
+        message_data.data = serde_json::json!({ "some_attribute": 1 });
+
+        let result = controls::messages::Example::from_message_data(&message_data);
+
+        assert!(matches!(result, Err(MessageError::DataError(_))));
+    }
+
+    #[test]
+    fn should_convert_message_into_write_message_data() {
+        let message = controls::messages::example_message();
+
+        let write_message_data = message.to_write_message_data().expect("conversion to work");
+
+        assert_eq!(write_message_data.message_type, "Example");
+        assert_eq!(
+            write_message_data.data,
+            controls::messages::example_message_data(0, 1).data
+        );
+        assert!(Uuid::parse_str(&write_message_data.id).is_ok());
+        assert_eq!(write_message_data.metadata, None);
+    }
+
+    #[test]
+    fn should_attach_metadata_to_write_message_data() {
+        let metadata = Metadata {
+            correlation_stream_name: Some("someCorrelation-123".to_string()),
+            ..Metadata::new()
+        };
+
+        let write_message_data = controls::messages::example_message()
+            .to_write_message_data()
+            .expect("conversion to work")
+            .with_metadata(metadata.clone());
+
+        assert_eq!(write_message_data.metadata, Some(metadata));
+    }
+}
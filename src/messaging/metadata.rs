@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::messaging::{MessageData, MessageError};
+
+/// The `metadata` of a message, stored with Message DB's (and Eventide's) camel cased keys
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub causation_message_stream_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub causation_message_position: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub causation_message_global_position: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_stream_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_stream_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, Value>,
+}
+
+impl Metadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Missing metadata is treated as empty metadata
+    pub fn from_message_data(message_data: &MessageData) -> Result<Self, MessageError> {
+        match &message_data.metadata {
+            Some(metadata) => Ok(serde_json::from_value(metadata.clone())?),
+            None => Ok(Self::new()),
+        }
+    }
+
+    /// Makes this message caused by `previous`, carrying its correlation and reply stream forward.
+    /// Fails, leaving this metadata as it was, when `previous` has metadata that doesn't parse
+    pub fn follow(&mut self, previous: &MessageData) -> Result<(), MessageError> {
+        let previous_metadata = Self::from_message_data(previous)?;
+
+        self.causation_message_stream_name = Some(previous.stream_name.clone());
+        self.causation_message_position = Some(previous.position);
+        self.causation_message_global_position = Some(previous.global_position);
+        self.correlation_stream_name = previous_metadata.correlation_stream_name;
+        self.reply_stream_name = previous_metadata.reply_stream_name;
+
+        Ok(())
+    }
+
+    /// Whether this message follows `previous` (see [`Metadata::follow`])
+    pub fn follows(&self, previous: &MessageData) -> bool {
+        self.causation_message_stream_name.as_deref() == Some(previous.stream_name.as_str())
+            && self.causation_message_position == Some(previous.position)
+            && self.causation_message_global_position == Some(previous.global_position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::controls;
+
+    #[test]
+    fn should_treat_missing_metadata_as_empty() {
+        let message_data = controls::messages::example_message_data(0, 1);
+
+        let metadata = Metadata::from_message_data(&message_data).expect("metadata to parse");
+
+        assert_eq!(metadata, Metadata::new());
+    }
+
+    #[test]
+    fn should_read_camel_cased_metadata() {
+        let mut message_data = controls::messages::example_message_data(0, 1);
+        message_data.metadata = Some(json!({
+            "correlationStreamName": "someCorrelation-123",
+            "schemaVersion": "1",
+            "properties": { "some_property": "some value" }
+        }));
+
+        let metadata = Metadata::from_message_data(&message_data).expect("metadata to parse");
+
+        assert_eq!(
+            metadata.correlation_stream_name.as_deref(),
+            Some("someCorrelation-123")
+        );
+        assert_eq!(metadata.schema_version.as_deref(), Some("1"));
+        assert_eq!(
+            metadata.properties.get("some_property"),
+            Some(&json!("some value"))
+        );
+    }
+
+    #[test]
+    fn should_only_write_keys_that_are_set() {
+        let metadata = Metadata {
+            reply_stream_name: Some("someReply-123".to_string()),
+            ..Metadata::new()
+        };
+
+        let value = serde_json::to_value(&metadata).expect("metadata to serialize");
+
+        assert_eq!(value, json!({ "replyStreamName": "someReply-123" }));
+    }
+
+    #[test]
+    fn should_copy_causation_and_correlation_when_following() {
+        let mut previous = controls::messages::example_message_data(3, 11);
+        previous.metadata = Some(json!({
+            "correlationStreamName": "someCorrelation-123",
+            "replyStreamName": "someReply-123"
+        }));
+
+        let mut metadata = Metadata::new();
+        metadata
+            .follow(&previous)
+            .expect("previous metadata to parse");
+
+        assert_eq!(
+            metadata.causation_message_stream_name,
+            Some(previous.stream_name.clone())
+        );
+        assert_eq!(metadata.causation_message_position, Some(3));
+        assert_eq!(metadata.causation_message_global_position, Some(11));
+        assert_eq!(
+            metadata.correlation_stream_name.as_deref(),
+            Some("someCorrelation-123")
+        );
+        assert_eq!(metadata.reply_stream_name.as_deref(), Some("someReply-123"));
+        assert!(metadata.follows(&previous));
+    }
+
+    #[test]
+    fn should_not_follow_a_message_with_invalid_metadata() {
+        let mut previous = controls::messages::example_message_data(3, 11);
+        previous.metadata = Some(json!({ "correlationStreamName": 123 }));
+
+        let mut metadata = Metadata::new();
+        let result = metadata.follow(&previous);
+
+        assert!(result.is_err());
+        assert_eq!(metadata, Metadata::new());
+    }
+}
@@ -1,6 +1,7 @@
 // use std::collections::HashMap;
-use std::error::Error as StdError;
 
+use postgres::Row;
+use serde_json::Value;
 use thiserror::Error;
 
 use crate::{
@@ -8,14 +9,24 @@ use crate::{
         get::{Get, GetError, GetTelemetry},
         MessageData,
     },
-    session::Session,
-    settings::Settings,
+    session::{Session, SessionError},
+    settings::{InvalidSettings, Settings, SettingsError},
 };
 
+pub mod stream;
+pub mod write;
+
+pub use stream::*;
+pub use write::*;
+
 #[derive(Error, Debug)]
 pub enum CategoryError {
     #[error("Session Error: {0}")]
-    SessionError(#[from] crate::session::SessionError),
+    SessionError(#[from] SessionError),
+    #[error("Settings Error: {0}")]
+    SettingsError(#[from] SettingsError),
+    #[error("{0}")]
+    InvalidSettings(#[from] InvalidSettings),
 }
 
 #[derive(Debug)]
@@ -27,24 +38,31 @@ pub struct Category {
 
 impl Category {
     pub fn build(category: impl Into<String>) -> Result<Self, CategoryError> {
-        Ok(Self {
-            category: category.into(),
-            settings: Settings::build(),
-            session: Session::build()?,
-        })
+        Self::build_params(category, Settings::build()?, Session::build()?)
     }
 
     pub fn build_params(
         category: impl Into<String>,
         settings: Settings,
-        session: Session,
+        mut session: Session,
     ) -> Result<Self, CategoryError> {
+        settings.validate()?;
+
+        if settings.condition.is_some() {
+            session.enable_sql_condition()?;
+        }
+
         Ok(Self {
             category: category.into(),
             settings,
             session,
         })
     }
+
+    /// How many times the session has reconnected after losing its connection
+    pub fn reconnect_count(&self) -> u64 {
+        self.session.reconnect_count()
+    }
 }
 
 impl Get for Category {
@@ -73,23 +91,76 @@ impl Get for Category {
             &[ &self.category, &position, &batch_size, &correlation, &consumer_group_member, &consumer_group_size, &condition ])
             .map_err(|error| {
                 log::error!("THIS ERROR HAPPENED: {}", error);
-                Box::new(error) as Box<dyn StdError + Send + Sync>
+                GetError::from(error)
             })?;
 
         log::trace!("Rows Returned: {:?}", rows);
 
-        Ok(rows
-            .into_iter()
-            .map(|row| {
-                let global_position: i64 = row.get("global_position");
-                MessageData {
-                    global_position: global_position as u64,
-                }
-            })
-            .collect())
+        rows.iter().map(message_data_from_row).collect()
+    }
+}
+
+/// The most recent message written to the stream
+pub fn get_last_stream_message(
+    session: &mut Session,
+    stream_name: &str,
+) -> Result<Option<MessageData>, GetError> {
+    let rows = session.query(
+        "SELECT * FROM get_last_stream_message($1::varchar);",
+        &[&stream_name],
+    )?;
+
+    rows.first().map(message_data_from_row).transpose()
+}
+
+/// The position of the last message written to the stream, `None` when nothing has been written
+pub fn stream_version(session: &mut Session, stream_name: &str) -> Result<Option<i64>, GetError> {
+    let rows = session.query(
+        "SELECT stream_version($1::varchar) AS stream_version;",
+        &[&stream_name],
+    )?;
+
+    Ok(rows.first().and_then(|row| row.get("stream_version")))
+}
+
+/// Keeps a lost connection apart from other failures, so a consumer can fetch again later
+impl From<SessionError> for GetError {
+    fn from(error: SessionError) -> Self {
+        if error.is_connection_lost() {
+            GetError::ConnectionLost(Box::new(error))
+        } else {
+            GetError::DataError(Box::new(error))
+        }
     }
 }
 
+fn message_data_from_row(row: &Row) -> Result<MessageData, GetError> {
+    let position: i64 = row.get("position");
+    let global_position: i64 = row.get("global_position");
+    let data: Option<String> = row.get("data");
+    let metadata: Option<String> = row.get("metadata");
+
+    let data = match data {
+        Some(data) => serde_json::from_str(&data)?,
+        None => Value::Null,
+    };
+    let metadata = match metadata {
+        Some(metadata) => Some(serde_json::from_str(&metadata)?),
+        None => None,
+    };
+
+    Ok(MessageData {
+        id: row.get("id"),
+        stream_name: row.get("stream_name"),
+        message_type: row.get("type"),
+        position: position as u64,
+        global_position: global_position as u64,
+        data,
+        metadata,
+        time: row.get("time"),
+    })
+}
+
 impl GetTelemetry for Category {
     fn record_get(&mut self) {}
 
@@ -101,7 +172,11 @@ mod integration_tests {
     use std::collections::HashMap;
 
     use super::*;
-    use crate::{controls, settings::Settings};
+    use crate::{
+        controls,
+        messaging::{Condition, Message, Write},
+        settings::Settings,
+    };
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -141,11 +216,6 @@ mod integration_tests {
         assert_eq!(messages.len(), one_message);
     }
 
-    // TODO: put in a writer test
-    // #[test]
-    // #[ignore]
-    // fn should_properly_return_an_expected_version_error_when_expected_version_is_incorrect() {}
-
     #[test]
     fn should_get_multiple_messages_when_multiple_in_stream() {
         init();
@@ -210,7 +280,7 @@ mod integration_tests {
         let category = controls::messages::postgres::write_random_message_to_random_category();
         controls::messages::postgres::write_random_message_with_correlation_to_category(
             &category,
-            &correlation,
+            correlation,
         );
 
         let category_count = controls::messages::postgres::category_count(&category);
@@ -291,9 +361,38 @@ mod integration_tests {
         let mut settings = Settings::new();
         settings.condition = Some(condition);
 
-        let mut session = Session::build().expect("to build a session");
+        let session = Session::build().expect("to build a session");
+
+        let mut category_get =
+            Category::build_params(category, settings, session).expect("category to build");
 
-        controls::messages::postgres::enable_condition_for_session(&mut session);
+        // Act
+        let beginning_position = 0;
+        let messages = category_get.get(beginning_position).expect("get to work");
+
+        // Assert
+        let consumer_message_count = 1;
+        assert_eq!(messages.len(), consumer_message_count);
+    }
+
+    #[test]
+    fn should_get_only_applicable_message_when_condition_built() {
+        init();
+
+        // Arrange
+        let mut data = HashMap::new();
+        data.insert("test", "it's true");
+        let condition = Condition::data("test").eq("it's true");
+
+        let category =
+            controls::messages::postgres::write_one_random_message_with_data_to_category(data);
+
+        controls::messages::postgres::write_random_message_to_category(&category);
+
+        let mut settings = Settings::new();
+        settings.condition = Some(condition.into());
+
+        let session = Session::build().expect("to build a session");
 
         let mut category_get =
             Category::build_params(category, settings, session).expect("category to build");
@@ -307,6 +406,34 @@ mod integration_tests {
         assert_eq!(messages.len(), consumer_message_count);
     }
 
+    #[test]
+    fn should_skip_messages_a_built_condition_cannot_cast() {
+        init();
+
+        // Arrange
+        let mut data = HashMap::new();
+        data.insert("amount", "not a number");
+        let condition = Condition::data("amount").gt(100);
+
+        let category =
+            controls::messages::postgres::write_one_random_message_with_data_to_category(data);
+
+        let mut settings = Settings::new();
+        settings.condition = Some(condition.into());
+
+        let session = Session::build().expect("to build a session");
+
+        let mut category_get =
+            Category::build_params(category, settings, session).expect("category to build");
+
+        // Act
+        let beginning_position = 0;
+        let messages = category_get.get(beginning_position).expect("get to work");
+
+        // Assert
+        assert!(messages.is_empty());
+    }
+
     #[test]
     fn should_get_none_when_position_more_than_in_stream() {
         init();
@@ -383,4 +510,152 @@ mod integration_tests {
             assert!(message.global_position as i64 > starting_global_position);
         }
     }
+
+    #[test]
+    fn should_get_messages_with_every_column_set() {
+        init();
+
+        // Arrange
+        let mut data = HashMap::new();
+        data.insert("test", "true");
+
+        let category =
+            controls::messages::postgres::write_one_random_message_with_data_to_category(data);
+
+        let mut category_get = Category::build(&category).expect("category to build");
+
+        // Act
+        let beginning_position = 0;
+        let messages = category_get.get(beginning_position).expect("get to work");
+
+        // Assert
+        let message = messages.first().expect("a message to be returned");
+        assert!(!message.id.is_empty());
+        assert!(message.stream_name.starts_with(&format!("{}-", category)));
+        assert_eq!(message.message_type, "Random");
+        assert_eq!(message.position, 0);
+        assert_eq!(message.data, serde_json::json!({ "test": "true" }));
+        assert_eq!(message.metadata, None);
+        assert!(message.time <= std::time::SystemTime::now());
+    }
+
+    #[test]
+    fn should_get_the_last_message_written_to_a_stream() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut writer = Writer::build().expect("writer to build");
+        let mut last_id = String::new();
+        for _ in 0..2 {
+            let message_data = controls::messages::example_message()
+                .to_write_message_data()
+                .expect("conversion to work");
+            writer
+                .write(&message_data, &stream_name, None)
+                .expect("write to work");
+            last_id = message_data.id;
+        }
+
+        let mut session = Session::build().expect("session to be built");
+
+        // Act
+        let message = get_last_stream_message(&mut session, &stream_name).expect("get to work");
+
+        // Assert
+        let message = message.expect("a message to be returned");
+        assert_eq!(message.id, last_id);
+        assert_eq!(message.position, 1);
+    }
+
+    #[test]
+    fn should_get_no_last_message_when_stream_is_empty() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut session = Session::build().expect("session to be built");
+
+        // Act
+        let message = get_last_stream_message(&mut session, &stream_name).expect("get to work");
+
+        // Assert
+        assert_eq!(message, None);
+    }
+
+    #[test]
+    fn should_get_the_stream_version_of_the_last_message() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut writer = Writer::build().expect("writer to build");
+        let mut last_position = 0;
+        for _ in 0..2 {
+            let message_data = controls::messages::example_message()
+                .to_write_message_data()
+                .expect("conversion to work");
+            last_position = writer
+                .write(&message_data, &stream_name, None)
+                .expect("write to work");
+        }
+
+        let mut session = Session::build().expect("session to be built");
+
+        // Act
+        let version = stream_version(&mut session, &stream_name).expect("stream version to work");
+
+        // Assert
+        assert_eq!(version, Some(last_position as i64));
+    }
+
+    #[test]
+    fn should_get_no_stream_version_when_stream_is_empty() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut session = Session::build().expect("session to be built");
+
+        // Act
+        let version = stream_version(&mut session, &stream_name).expect("stream version to work");
+
+        // Assert
+        assert_eq!(version, None);
+    }
+
+    #[test]
+    fn should_reconnect_and_get_when_the_connection_is_lost() {
+        init();
+
+        // Arrange
+        let category = controls::messages::postgres::write_random_message_to_random_category();
+
+        let mut settings = Settings::build().expect("settings to build");
+        settings.reconnect_interval_ms = 10;
+        let mut session = Session::build_params(&settings).expect("session to be built");
+        let rows = session
+            .query("SELECT pg_backend_pid();", &[])
+            .expect("query to work");
+        let pid: i32 = rows.first().expect("a row").get(0);
+
+        let mut category_get =
+            Category::build_params(&category, settings, session).expect("category to build");
+
+        Session::build()
+            .expect("session to be built")
+            .query("SELECT pg_terminate_backend($1);", &[&pid])
+            .expect("terminate to work");
+
+        // Act
+        let messages = category_get.get(1).expect("get to work");
+
+        // Assert
+        assert_eq!(messages.len(), 1);
+        assert_eq!(category_get.reconnect_count(), 1);
+    }
 }
@@ -0,0 +1,272 @@
+use thiserror::Error;
+
+use crate::{
+    messaging::{
+        get::{Get, GetError, GetTelemetry, BEGINNING_STREAM_POSITION},
+        MessageData,
+    },
+    session::{Session, SessionError},
+    settings::{InvalidSettings, Settings, SettingsError},
+};
+
+use super::message_data_from_row;
+
+#[derive(Error, Debug)]
+pub enum StreamError {
+    #[error("Session Error: {0}")]
+    SessionError(#[from] SessionError),
+    #[error("Settings Error: {0}")]
+    SettingsError(#[from] SettingsError),
+    #[error("{0}")]
+    InvalidSettings(#[from] InvalidSettings),
+}
+
+/// Reads the messages of a single stream. Positions are stream positions rather than global positions
+#[derive(Debug)]
+pub struct Stream {
+    stream_name: String,
+    settings: Settings,
+    session: Session,
+}
+
+impl Stream {
+    pub fn build(stream_name: impl Into<String>) -> Result<Self, StreamError> {
+        Self::build_params(stream_name, Settings::build()?, Session::build()?)
+    }
+
+    pub fn build_params(
+        stream_name: impl Into<String>,
+        settings: Settings,
+        mut session: Session,
+    ) -> Result<Self, StreamError> {
+        settings.validate()?;
+
+        if settings.condition.is_some() {
+            session.enable_sql_condition()?;
+        }
+
+        Ok(Self {
+            stream_name: stream_name.into(),
+            settings,
+            session,
+        })
+    }
+
+    /// How many times the session has reconnected after losing its connection
+    pub fn reconnect_count(&self) -> u64 {
+        self.session.reconnect_count()
+    }
+}
+
+impl Get for Stream {
+    fn get(&mut self, position: i64) -> Result<Vec<MessageData>, GetError> {
+        /*
+        stream_name varchar,
+        "position" bigint DEFAULT 0,
+        batch_size bigint DEFAULT 1000,
+        condition varchar DEFAULT NULL
+         */
+        let batch_size: Option<i64> = self.settings.batch_size.map(|bs| bs as i64);
+        let condition: &Option<String> = &self.settings.condition;
+
+        let rows = self
+            .session
+            .query(
+                "SELECT * FROM get_stream_messages($1::varchar, $2::bigint, $3::bigint, $4::varchar);",
+                &[&self.stream_name, &position, &batch_size, &condition],
+            )
+            .map_err(|error| {
+                log::error!("Unable to get stream messages: {}", error);
+                GetError::from(error)
+            })?;
+
+        log::trace!("Rows Returned: {:?}", rows);
+
+        rows.iter().map(message_data_from_row).collect()
+    }
+
+    fn beginning_position(&self) -> u64 {
+        BEGINNING_STREAM_POSITION
+    }
+
+    fn message_position(&self, message_data: &MessageData) -> u64 {
+        message_data.position
+    }
+}
+
+impl GetTelemetry for Stream {
+    fn record_get(&mut self) {}
+
+    fn record_got_messages(&mut self, _messages: &[MessageData]) {}
+}
+
+#[cfg(all(test, feature = "integration_tests"))]
+mod integration_tests {
+    use super::*;
+    use crate::{
+        controls,
+        messaging::{postgres::Writer, Message, Write, WriteMessageData},
+    };
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn write_messages(stream_name: &str, count: u64) -> Vec<WriteMessageData> {
+        let mut writer = Writer::build().expect("writer to build");
+
+        (0..count)
+            .map(|_| {
+                let message_data = controls::messages::example_message()
+                    .to_write_message_data()
+                    .expect("conversion to work");
+                writer
+                    .write(&message_data, stream_name, None)
+                    .expect("write to work");
+                message_data
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_get_no_messages_when_stream_is_empty() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut stream_get = Stream::build(stream_name).expect("stream to build");
+
+        // Act
+        let messages = stream_get
+            .get(BEGINNING_STREAM_POSITION as i64)
+            .expect("get to work");
+
+        // Assert
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn should_only_get_messages_from_the_stream() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let other_stream_name = controls::category::unique_stream_name(&category);
+        let written = write_messages(&stream_name, 2);
+        write_messages(&other_stream_name, 1);
+
+        let mut stream_get = Stream::build(&stream_name).expect("stream to build");
+
+        // Act
+        let messages = stream_get
+            .get(BEGINNING_STREAM_POSITION as i64)
+            .expect("get to work");
+
+        // Assert
+        let ids: Vec<_> = messages.iter().map(|message| message.id.clone()).collect();
+        let written_ids: Vec<_> = written.iter().map(|message| message.id.clone()).collect();
+        assert_eq!(ids, written_ids);
+    }
+
+    #[test]
+    fn should_get_messages_from_the_stream_position() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        write_messages(&stream_name, 3);
+
+        let mut stream_get = Stream::build(&stream_name).expect("stream to build");
+
+        // Act
+        let messages = stream_get.get(1).expect("get to work");
+
+        // Assert
+        let positions: Vec<_> = messages
+            .iter()
+            .map(|message| stream_get.message_position(message))
+            .collect();
+        assert_eq!(positions, vec![1, 2]);
+    }
+
+    #[test]
+    fn should_limit_get_when_batch_size_less_then_in_stream() {
+        init();
+
+        // Arrange
+        let batch_size = 2;
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        write_messages(&stream_name, batch_size * 2);
+
+        let mut settings = Settings::new();
+        settings.batch_size = Some(batch_size);
+        let session = Session::build().expect("session to be built");
+
+        let mut stream_get =
+            Stream::build_params(&stream_name, settings, session).expect("stream to build");
+
+        // Act
+        let messages = stream_get
+            .get(BEGINNING_STREAM_POSITION as i64)
+            .expect("get to work");
+
+        // Assert
+        assert_eq!(messages.len(), batch_size as usize);
+    }
+
+    #[test]
+    fn should_only_get_messages_matching_the_condition() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        write_messages(&stream_name, 1);
+
+        let mut writer = Writer::build().expect("writer to build");
+        let other_message = controls::messages::Example {
+            some_attribute: "other value".to_string(),
+        }
+        .to_write_message_data()
+        .expect("conversion to work");
+        writer
+            .write(&other_message, &stream_name, None)
+            .expect("write to work");
+
+        let mut settings = Settings::new();
+        settings.condition = Some("data->>'some_attribute' = 'other value'".to_string());
+        let session = Session::build().expect("session to be built");
+
+        let mut stream_get =
+            Stream::build_params(&stream_name, settings, session).expect("stream to build");
+
+        // Act
+        let messages = stream_get
+            .get(BEGINNING_STREAM_POSITION as i64)
+            .expect("get to work");
+
+        // Assert
+        let ids: Vec<_> = messages.iter().map(|message| message.id.clone()).collect();
+        assert_eq!(ids, vec![other_message.id]);
+    }
+
+    #[test]
+    fn should_reject_invalid_settings() {
+        init();
+
+        // Arrange
+        let mut settings = Settings::new();
+        settings.batch_size = Some(0);
+        let session = Session::build().expect("session to be built");
+
+        // Act
+        let result = Stream::build_params("someStream-123", settings, session);
+
+        // Assert
+        assert!(matches!(result, Err(StreamError::InvalidSettings(_))));
+    }
+}
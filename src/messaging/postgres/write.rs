@@ -0,0 +1,394 @@
+use std::error::Error as StdError;
+
+use postgres::Row;
+use serde_json::Value;
+
+use crate::{
+    messaging::{
+        write::{Write, WriteError, WriteTelemetry},
+        WriteMessageData,
+    },
+    session::{Session, SessionError},
+};
+
+const WRITE_MESSAGE_QUERY: &str = "SELECT write_message($1::varchar, $2::varchar, $3::varchar, $4::jsonb, $5::jsonb, $6::bigint);";
+const EXPECTED_VERSION_ERROR_PREFIX: &str = "Wrong expected version";
+
+#[derive(Debug)]
+pub struct Writer {
+    session: Session,
+}
+
+impl Writer {
+    pub fn build() -> Result<Self, SessionError> {
+        Ok(Self {
+            session: Session::build()?,
+        })
+    }
+
+    pub fn build_params(session: Session) -> Self {
+        Self { session }
+    }
+}
+
+impl Write for Writer {
+    fn write(
+        &mut self,
+        message_data: &WriteMessageData,
+        stream_name: &str,
+        expected_version: Option<i64>,
+    ) -> Result<u64, WriteError> {
+        let position = write_message(
+            &mut self.session,
+            message_data,
+            stream_name,
+            expected_version,
+        )?;
+
+        self.record_written(stream_name, message_data, expected_version, position);
+
+        Ok(position)
+    }
+
+    fn write_batch(
+        &mut self,
+        messages: &[WriteMessageData],
+        stream_name: &str,
+        expected_version: Option<i64>,
+    ) -> Result<u64, WriteError> {
+        if messages.is_empty() {
+            return Err(WriteError::EmptyBatch);
+        }
+
+        let positions = self.session.transaction(|transaction| {
+            let mut positions = Vec::with_capacity(messages.len());
+
+            for (index, message_data) in messages.iter().enumerate() {
+                let metadata = metadata_value(message_data)?;
+                let expected_version = if index == 0 { expected_version } else { None };
+
+                let rows = transaction.query(
+                    WRITE_MESSAGE_QUERY,
+                    &[
+                        &message_data.id,
+                        &stream_name,
+                        &message_data.message_type,
+                        &message_data.data,
+                        &metadata,
+                        &expected_version,
+                    ],
+                )?;
+
+                positions.push(position_from_rows(&rows)?);
+            }
+
+            Ok::<_, WriteError>(positions)
+        })?;
+
+        for (index, (message_data, position)) in messages.iter().zip(&positions).enumerate() {
+            let expected_version = if index == 0 { expected_version } else { None };
+            self.record_written(stream_name, message_data, expected_version, *position);
+        }
+
+        Ok(*positions.last().expect("batch to not be empty"))
+    }
+}
+
+/// Writes a single message with `write_message`, returning its position in the stream
+pub fn write_message(
+    session: &mut Session,
+    message_data: &WriteMessageData,
+    stream_name: &str,
+    expected_version: Option<i64>,
+) -> Result<u64, WriteError> {
+    let metadata = metadata_value(message_data)?;
+
+    let rows = session.query_once(
+        WRITE_MESSAGE_QUERY,
+        &[
+            &message_data.id,
+            &stream_name,
+            &message_data.message_type,
+            &message_data.data,
+            &metadata,
+            &expected_version,
+        ],
+    )?;
+
+    position_from_rows(&rows)
+}
+
+fn metadata_value(message_data: &WriteMessageData) -> Result<Option<Value>, WriteError> {
+    Ok(message_data
+        .metadata
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|error| Box::new(error) as Box<dyn StdError + Send + Sync>)?)
+}
+
+fn position_from_rows(rows: &[Row]) -> Result<u64, WriteError> {
+    let position: i64 = rows.first().map(|row| row.get(0)).ok_or_else(|| {
+        Box::<dyn StdError + Send + Sync>::from("write_message returned no position")
+    })?;
+
+    Ok(position as u64)
+}
+
+impl From<SessionError> for WriteError {
+    fn from(error: SessionError) -> Self {
+        match error {
+            SessionError::PostgresError(error) => match error.as_db_error() {
+                Some(db_error)
+                    if db_error
+                        .message()
+                        .starts_with(EXPECTED_VERSION_ERROR_PREFIX) =>
+                {
+                    WriteError::ExpectedVersionError(db_error.message().to_string())
+                }
+                _ => WriteError::DataError(Box::new(error)),
+            },
+            other => WriteError::DataError(Box::new(other)),
+        }
+    }
+}
+
+impl WriteTelemetry for Writer {
+    fn record_written(
+        &mut self,
+        _stream_name: &str,
+        _message_data: &WriteMessageData,
+        _expected_version: Option<i64>,
+        _position: u64,
+    ) {
+    }
+}
+
+#[cfg(all(test, feature = "integration_tests"))]
+mod integration_tests {
+    use super::*;
+    use crate::{
+        controls,
+        messaging::{get::Get, postgres::Category, Message, Metadata, NO_STREAM},
+    };
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn example_write_message_data() -> WriteMessageData {
+        controls::messages::example_message()
+            .to_write_message_data()
+            .expect("conversion to work")
+    }
+
+    #[test]
+    fn should_write_a_message_that_can_be_read_back() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let metadata = Metadata {
+            correlation_stream_name: Some("someCorrelation-123".to_string()),
+            ..Metadata::new()
+        };
+        let message_data = example_write_message_data().with_metadata(metadata.clone());
+
+        let mut writer = Writer::build().expect("writer to build");
+
+        // Act
+        writer
+            .write(&message_data, &stream_name, None)
+            .expect("write to work");
+
+        // Assert
+        let mut category_get = Category::build(category).expect("category to build");
+        let messages = category_get.get(0).expect("get to work");
+        let message = messages.first().expect("a message to be written");
+
+        assert_eq!(message.id, message_data.id);
+        assert_eq!(message.stream_name, stream_name);
+        assert_eq!(message.message_type, message_data.message_type);
+        assert_eq!(message.data, message_data.data);
+        assert_eq!(
+            Metadata::from_message_data(message).expect("metadata to parse"),
+            metadata
+        );
+    }
+
+    #[test]
+    fn should_return_the_stream_position_of_the_written_message() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+
+        let mut writer = Writer::build().expect("writer to build");
+
+        // Act
+        let first_position = writer
+            .write(&example_write_message_data(), &stream_name, None)
+            .expect("write to work");
+        let second_position = writer
+            .write(&example_write_message_data(), &stream_name, None)
+            .expect("write to work");
+
+        // Assert
+        assert_eq!(first_position, 0);
+        assert_eq!(second_position, 1);
+    }
+
+    #[test]
+    fn should_write_when_expected_version_matches() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+
+        let mut writer = Writer::build().expect("writer to build");
+
+        // Act
+        let first_position = writer
+            .write(&example_write_message_data(), &stream_name, Some(NO_STREAM))
+            .expect("write to work");
+        let second_position = writer
+            .write(
+                &example_write_message_data(),
+                &stream_name,
+                Some(first_position as i64),
+            )
+            .expect("write to work");
+
+        // Assert
+        assert_eq!(second_position, first_position + 1);
+    }
+
+    #[test]
+    fn should_properly_return_an_expected_version_error_when_expected_version_is_incorrect() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+
+        let mut writer = Writer::build().expect("writer to build");
+        writer
+            .write(&example_write_message_data(), &stream_name, None)
+            .expect("write to work");
+
+        // Act
+        let result = writer.write(&example_write_message_data(), &stream_name, Some(NO_STREAM));
+
+        // Assert
+        assert!(
+            matches!(result, Err(WriteError::ExpectedVersionError(_))),
+            "expected an expected version error, got {:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn should_write_every_message_in_a_batch() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let messages = vec![example_write_message_data(), example_write_message_data()];
+
+        let mut writer = Writer::build().expect("writer to build");
+
+        // Act
+        let last_position = writer
+            .write_batch(&messages, &stream_name, Some(NO_STREAM))
+            .expect("batch write to work");
+
+        // Assert
+        assert_eq!(last_position, 1);
+        assert_eq!(controls::messages::postgres::category_count(&category), 2);
+    }
+
+    #[test]
+    fn should_only_check_expected_version_against_the_first_message_in_a_batch() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut writer = Writer::build().expect("writer to build");
+        let first_position = writer
+            .write(&example_write_message_data(), &stream_name, None)
+            .expect("write to work");
+
+        let messages = vec![example_write_message_data(), example_write_message_data()];
+
+        // Act
+        let last_position = writer
+            .write_batch(&messages, &stream_name, Some(first_position as i64))
+            .expect("batch write to work");
+
+        // Assert
+        assert_eq!(last_position, first_position + 2);
+    }
+
+    #[test]
+    fn should_write_none_of_a_batch_when_expected_version_is_incorrect() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut writer = Writer::build().expect("writer to build");
+        writer
+            .write(&example_write_message_data(), &stream_name, None)
+            .expect("write to work");
+
+        let messages = vec![example_write_message_data(), example_write_message_data()];
+
+        // Act
+        let result = writer.write_batch(&messages, &stream_name, Some(NO_STREAM));
+
+        // Assert
+        assert!(matches!(result, Err(WriteError::ExpectedVersionError(_))));
+        assert_eq!(controls::messages::postgres::category_count(&category), 1);
+    }
+
+    #[test]
+    fn should_write_none_of_a_batch_when_a_later_message_fails() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let duplicate = example_write_message_data();
+        let messages = vec![duplicate.clone(), duplicate];
+
+        let mut writer = Writer::build().expect("writer to build");
+
+        // Act
+        let result = writer.write_batch(&messages, &stream_name, None);
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(controls::messages::postgres::category_count(&category), 0);
+    }
+
+    #[test]
+    fn should_reject_an_empty_batch() {
+        init();
+
+        // Arrange
+        let category = controls::category::unique_category();
+        let stream_name = controls::category::unique_stream_name(&category);
+        let mut writer = Writer::build().expect("writer to build");
+
+        // Act
+        let result = writer.write_batch(&[], &stream_name, None);
+
+        // Assert
+        assert!(matches!(result, Err(WriteError::EmptyBatch)));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use crate::messaging::{
+    get::{Get, GetError},
+    postgres::{Stream, StreamError},
+    MessageData,
+};
+
+/// Reads every message from a position onwards, one batch at a time, ending at the first empty batch.
+/// Batch size is whatever the `Get` was built with (`Settings::batch_size`)
+#[derive(Debug)]
+pub struct Read<G: Get> {
+    get: G,
+    position: u64,
+    batch: VecDeque<MessageData>,
+    finished: bool,
+}
+
+impl Read<Stream> {
+    pub fn build(stream_name: impl Into<String>) -> Result<Self, StreamError> {
+        Ok(Self::new(Stream::build(stream_name)?))
+    }
+}
+
+impl<G: Get> Read<G> {
+    pub fn new(get: G) -> Self {
+        let position = get.beginning_position();
+        Self::build_params(get, position)
+    }
+
+    pub fn build_params(get: G, position: u64) -> Self {
+        Self {
+            get,
+            position,
+            batch: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// The position the next batch will be read from
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn into_get(self) -> G {
+        self.get
+    }
+}
+
+impl<G: Get> Iterator for Read<G> {
+    type Item = Result<MessageData, GetError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.batch.is_empty() && !self.finished {
+            match self.get.get(self.position as i64) {
+                Ok(messages) if messages.is_empty() => self.finished = true,
+                Ok(messages) => self.batch.extend(messages),
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        let message_data = self.batch.pop_front()?;
+        self.position = self.get.message_position(&message_data) + 1;
+
+        Some(Ok(message_data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{controls, messaging::SubstituteGetter};
+
+    #[test]
+    fn should_read_every_message() {
+        let messages = controls::messages::example();
+        let mut get = SubstituteGetter::new("my_category");
+        get.queue_messages(&messages);
+
+        let read_messages: Result<Vec<_>, _> = Read::new(get).collect();
+
+        assert_eq!(read_messages.expect("read to work"), messages);
+    }
+
+    #[test]
+    fn should_stop_reading_at_the_first_empty_batch() {
+        let mut get = SubstituteGetter::new("my_category");
+        get.queue_messages(&controls::messages::example());
+
+        let mut read = Read::new(get);
+        read.by_ref().for_each(drop);
+        assert!(read.next().is_none());
+
+        let get = read.into_get();
+        let batch_and_empty_batch = 2;
+        assert_eq!(get.get_count(), batch_and_empty_batch);
+    }
+
+    #[test]
+    fn should_read_from_the_starting_position() {
+        let messages = controls::messages::example();
+        let mut get = SubstituteGetter::new("my_category");
+        get.queue_messages(&messages);
+
+        let starting_position = messages[1].global_position;
+        let read_messages: Result<Vec<_>, _> = Read::build_params(get, starting_position).collect();
+
+        assert_eq!(read_messages.expect("read to work"), messages[1..]);
+    }
+
+    #[test]
+    fn should_track_position_after_the_last_message_read() {
+        let messages = controls::messages::example();
+        let mut get = SubstituteGetter::new("my_category");
+        get.queue_messages(&messages);
+
+        let mut read = Read::new(get);
+        read.next();
+
+        assert_eq!(read.position(), messages[0].global_position + 1);
+    }
+}
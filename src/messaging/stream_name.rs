@@ -0,0 +1,347 @@
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+const ID_SEPARATOR: char = '-';
+const TYPE_SEPARATOR: char = ':';
+const COMPOUND_SEPARATOR: char = '+';
+
+/// A Message DB stream name: `entity[:type1[+type2]][-id1[+id2]]`
+///
+/// The category is everything before the first `-`, the id everything after it, and the cardinal
+/// id is the first of the (`+` separated) compound ids. It derefs to the composed `&str`, so it
+/// can be passed wherever a stream name string is expected, e.g. to `Write::write`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StreamName {
+    entity: String,
+    types: Vec<String>,
+    ids: Vec<String>,
+    name: String,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum StreamNameError {
+    #[error("Stream name must have a category")]
+    MissingCategory,
+    #[error("Category entity {0:?} must not be empty or contain '-', ':' or '+'")]
+    InvalidEntity(String),
+    #[error("Category type {0:?} must not be empty or contain '-', ':' or '+'")]
+    InvalidType(String),
+    #[error("Stream id {0:?} must not be empty or contain '+'")]
+    InvalidId(String),
+}
+
+impl StreamName {
+    /// A category stream name, e.g. `account`
+    pub fn new(entity: impl Into<String>) -> Result<Self, StreamNameError> {
+        let entity = entity.into();
+        if !is_valid_category_part(&entity) {
+            return Err(StreamNameError::InvalidEntity(entity));
+        }
+
+        Ok(Self {
+            name: entity.clone(),
+            entity,
+            types: vec![],
+            ids: vec![],
+        })
+    }
+
+    pub fn parse(stream_name: &str) -> Result<Self, StreamNameError> {
+        if stream_name.is_empty() {
+            return Err(StreamNameError::MissingCategory);
+        }
+
+        let (category, id) = match stream_name.split_once(ID_SEPARATOR) {
+            Some((category, id)) => (category, Some(id)),
+            None => (stream_name, None),
+        };
+
+        if category.is_empty() {
+            return Err(StreamNameError::MissingCategory);
+        }
+
+        let mut parsed = match category.split_once(TYPE_SEPARATOR) {
+            Some((entity, types)) => types
+                .split(COMPOUND_SEPARATOR)
+                .try_fold(Self::new(entity)?, |parsed, message_type| {
+                    parsed.with_type(message_type)
+                })?,
+            None => Self::new(category)?,
+        };
+
+        if let Some(id) = id {
+            parsed = id
+                .split(COMPOUND_SEPARATOR)
+                .try_fold(parsed, |parsed, id| parsed.with_id(id))?;
+        }
+
+        Ok(parsed)
+    }
+
+    /// Adds a type to the category, e.g. `account:command`
+    pub fn with_type(mut self, message_type: impl Into<String>) -> Result<Self, StreamNameError> {
+        let message_type = message_type.into();
+        if !is_valid_category_part(&message_type) {
+            return Err(StreamNameError::InvalidType(message_type));
+        }
+
+        self.types.push(message_type);
+        Ok(self.composed())
+    }
+
+    /// Adds an id, making a compound id when there is already one, e.g. `account-123+456`
+    pub fn with_id(mut self, id: impl Into<String>) -> Result<Self, StreamNameError> {
+        let id = id.into();
+        if id.is_empty() || id.contains(COMPOUND_SEPARATOR) {
+            return Err(StreamNameError::InvalidId(id));
+        }
+
+        self.ids.push(id);
+        Ok(self.composed())
+    }
+
+    pub fn entity(&self) -> &str {
+        &self.entity
+    }
+
+    pub fn types(&self) -> &[String] {
+        &self.types
+    }
+
+    pub fn has_type(&self, message_type: &str) -> bool {
+        self.types.iter().any(|existing| existing == message_type)
+    }
+
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    pub fn category(&self) -> String {
+        if self.types.is_empty() {
+            self.entity.clone()
+        } else {
+            format!(
+                "{}{}{}",
+                self.entity,
+                TYPE_SEPARATOR,
+                self.types.join(&COMPOUND_SEPARATOR.to_string())
+            )
+        }
+    }
+
+    pub fn id(&self) -> Option<String> {
+        if self.ids.is_empty() {
+            None
+        } else {
+            Some(self.ids.join(&COMPOUND_SEPARATOR.to_string()))
+        }
+    }
+
+    pub fn cardinal_id(&self) -> Option<&str> {
+        self.ids.first().map(String::as_str)
+    }
+
+    pub fn is_category(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// The category stream name of this stream
+    pub fn category_stream_name(&self) -> Self {
+        Self {
+            entity: self.entity.clone(),
+            types: self.types.clone(),
+            ids: vec![],
+            name: self.category(),
+        }
+    }
+
+    fn composed(mut self) -> Self {
+        self.name = match self.id() {
+            Some(id) => format!("{}{}{}", self.category(), ID_SEPARATOR, id),
+            None => self.category(),
+        };
+        self
+    }
+}
+
+fn is_valid_category_part(part: &str) -> bool {
+    !part.is_empty()
+        && !part.contains(ID_SEPARATOR)
+        && !part.contains(TYPE_SEPARATOR)
+        && !part.contains(COMPOUND_SEPARATOR)
+}
+
+impl fmt::Display for StreamName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl Deref for StreamName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl AsRef<str> for StreamName {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl FromStr for StreamName {
+    type Err = StreamNameError;
+
+    fn from_str(stream_name: &str) -> Result<Self, Self::Err> {
+        Self::parse(stream_name)
+    }
+}
+
+impl TryFrom<&str> for StreamName {
+    type Error = StreamNameError;
+
+    fn try_from(stream_name: &str) -> Result<Self, Self::Error> {
+        Self::parse(stream_name)
+    }
+}
+
+impl From<StreamName> for String {
+    fn from(stream_name: StreamName) -> Self {
+        stream_name.name
+    }
+}
+
+impl From<&StreamName> for String {
+    fn from(stream_name: &StreamName) -> Self {
+        stream_name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_a_category() {
+        let stream_name = StreamName::parse("account").expect("stream name to parse");
+
+        assert_eq!(stream_name.entity(), "account");
+        assert_eq!(stream_name.category(), "account");
+        assert_eq!(stream_name.id(), None);
+        assert_eq!(stream_name.cardinal_id(), None);
+        assert!(stream_name.is_category());
+    }
+
+    #[test]
+    fn should_parse_an_id_containing_dashes() {
+        let stream_name = StreamName::parse("account-00000000-0000-4000-8000-000000000000")
+            .expect("stream name to parse");
+
+        assert_eq!(stream_name.category(), "account");
+        assert_eq!(
+            stream_name.id().as_deref(),
+            Some("00000000-0000-4000-8000-000000000000")
+        );
+        assert!(!stream_name.is_category());
+    }
+
+    #[test]
+    fn should_parse_category_types() {
+        let stream_name =
+            StreamName::parse("account:command+position-123").expect("stream name to parse");
+
+        assert_eq!(stream_name.entity(), "account");
+        assert_eq!(stream_name.types(), ["command", "position"]);
+        assert!(stream_name.has_type("position"));
+        assert_eq!(stream_name.category(), "account:command+position");
+        assert_eq!(stream_name.id().as_deref(), Some("123"));
+    }
+
+    #[test]
+    fn should_parse_compound_ids() {
+        let stream_name = StreamName::parse("account-123+456").expect("stream name to parse");
+
+        assert_eq!(stream_name.ids(), ["123", "456"]);
+        assert_eq!(stream_name.id().as_deref(), Some("123+456"));
+        assert_eq!(stream_name.cardinal_id(), Some("123"));
+    }
+
+    #[test]
+    fn should_compose_the_same_stream_name_that_was_parsed() {
+        for raw in [
+            "account",
+            "account-123",
+            "account:command",
+            "account:command+position-123+456",
+        ] {
+            let stream_name = StreamName::parse(raw).expect("stream name to parse");
+            assert_eq!(stream_name.to_string(), raw);
+        }
+    }
+
+    #[test]
+    fn should_compose_a_stream_name() {
+        let stream_name = StreamName::new("account")
+            .and_then(|stream_name| stream_name.with_type("command"))
+            .and_then(|stream_name| stream_name.with_id("123"))
+            .and_then(|stream_name| stream_name.with_id("456"))
+            .expect("stream name to build");
+
+        assert_eq!(String::from(&stream_name), "account:command-123+456");
+        assert_eq!(&*stream_name, "account:command-123+456");
+        assert_eq!(
+            stream_name.category_stream_name().to_string(),
+            "account:command"
+        );
+    }
+
+    #[test]
+    fn should_reject_a_missing_category() {
+        assert_eq!(StreamName::parse(""), Err(StreamNameError::MissingCategory));
+        assert_eq!(
+            StreamName::parse("-123"),
+            Err(StreamNameError::MissingCategory)
+        );
+    }
+
+    #[test]
+    fn should_reject_empty_types_and_ids() {
+        assert_eq!(
+            StreamName::parse("account:-123"),
+            Err(StreamNameError::InvalidType("".to_string()))
+        );
+        assert_eq!(
+            StreamName::parse("account:command+-123"),
+            Err(StreamNameError::InvalidType("".to_string()))
+        );
+        assert_eq!(
+            StreamName::parse("account-"),
+            Err(StreamNameError::InvalidId("".to_string()))
+        );
+        assert_eq!(
+            StreamName::parse("account-123+"),
+            Err(StreamNameError::InvalidId("".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_reject_separators_when_composing() {
+        assert_eq!(
+            StreamName::new("my-account"),
+            Err(StreamNameError::InvalidEntity("my-account".to_string()))
+        );
+        assert!(matches!(
+            StreamName::new("account").and_then(|stream_name| stream_name.with_type("a:b")),
+            Err(StreamNameError::InvalidType(_))
+        ));
+        assert!(matches!(
+            StreamName::new("account").and_then(|stream_name| stream_name.with_id("1+2")),
+            Err(StreamNameError::InvalidId(_))
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+use thiserror::Error;
+
+use std::error::Error as StdError;
+use std::sync::{Arc, Mutex};
+
+use crate::messaging::{Message, WriteMessageData};
+
+/// Expected version of a stream that has not been written to yet
+pub const NO_STREAM: i64 = -1;
+
+pub trait Write: WriteTelemetry {
+    /// Returns the position of the written message in its stream
+    fn write(
+        &mut self,
+        message_data: &WriteMessageData,
+        stream_name: &str,
+        expected_version: Option<i64>,
+    ) -> Result<u64, WriteError>;
+
+    /// Writes all of the messages or none of them. The expected version only applies to the
+    /// first message. Returns the position of the last written message in its stream
+    fn write_batch(
+        &mut self,
+        messages: &[WriteMessageData],
+        stream_name: &str,
+        expected_version: Option<i64>,
+    ) -> Result<u64, WriteError>;
+}
+
+#[derive(Error, Debug)]
+pub enum WriteError {
+    #[error("Expected version does not match the stream: {0}")]
+    ExpectedVersionError(String),
+    #[error("A batch must contain at least one message")]
+    EmptyBatch,
+    #[error("An error writing data occurred: {0}")]
+    DataError(#[from] Box<dyn StdError + Send + Sync>),
+}
+
+pub trait WriteTelemetry {
+    fn record_written(
+        &mut self,
+        stream_name: &str,
+        message_data: &WriteMessageData,
+        expected_version: Option<i64>,
+        position: u64,
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Written {
+    pub stream_name: String,
+    pub expected_version: Option<i64>,
+    pub message_data: WriteMessageData,
+    pub position: u64,
+}
+
+/// Records writes instead of sending them to the message store. Clones share their records
+#[derive(Debug, Clone, Default)]
+pub struct SubstituteWriter {
+    writes: Arc<Mutex<Vec<Written>>>,
+}
+
+impl SubstituteWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn writes(&self) -> Vec<Written> {
+        self.writes
+            .lock()
+            .expect("mutex to not be poisoned")
+            .clone()
+    }
+
+    pub fn written_to(&self, stream_name: &str) -> Vec<WriteMessageData> {
+        self.writes()
+            .into_iter()
+            .filter(|written| written.stream_name == stream_name)
+            .map(|written| written.message_data)
+            .collect()
+    }
+
+    /// Whether any write matches the predicate
+    pub fn written(&self, predicate: impl Fn(&Written) -> bool) -> bool {
+        self.writes
+            .lock()
+            .expect("mutex to not be poisoned")
+            .iter()
+            .any(predicate)
+    }
+
+    pub fn messages_of_type<M: Message>(&self) -> Vec<M> {
+        self.writes()
+            .into_iter()
+            .filter(|written| written.message_data.message_type == M::TYPE_NAME)
+            .filter_map(|written| serde_json::from_value(written.message_data.data).ok())
+            .collect()
+    }
+
+    /// The written message of type `M`, or `None` unless exactly one was written
+    pub fn one_message_of_type<M: Message>(&self) -> Option<M> {
+        let mut messages = self.messages_of_type::<M>();
+
+        if messages.len() == 1 {
+            messages.pop()
+        } else {
+            None
+        }
+    }
+
+    fn next_position(&self, stream_name: &str) -> u64 {
+        self.writes
+            .lock()
+            .expect("mutex to not be poisoned")
+            .iter()
+            .filter(|written| written.stream_name == stream_name)
+            .count() as u64
+    }
+}
+
+impl Write for SubstituteWriter {
+    fn write(
+        &mut self,
+        message_data: &WriteMessageData,
+        stream_name: &str,
+        expected_version: Option<i64>,
+    ) -> Result<u64, WriteError> {
+        let position = self.next_position(stream_name);
+
+        self.record_written(stream_name, message_data, expected_version, position);
+
+        Ok(position)
+    }
+
+    fn write_batch(
+        &mut self,
+        messages: &[WriteMessageData],
+        stream_name: &str,
+        expected_version: Option<i64>,
+    ) -> Result<u64, WriteError> {
+        let mut position = None;
+
+        for (index, message_data) in messages.iter().enumerate() {
+            let expected_version = if index == 0 { expected_version } else { None };
+            position = Some(self.write(message_data, stream_name, expected_version)?);
+        }
+
+        position.ok_or(WriteError::EmptyBatch)
+    }
+}
+
+impl WriteTelemetry for SubstituteWriter {
+    fn record_written(
+        &mut self,
+        stream_name: &str,
+        message_data: &WriteMessageData,
+        expected_version: Option<i64>,
+        position: u64,
+    ) {
+        self.writes
+            .lock()
+            .expect("mutex to not be poisoned")
+            .push(Written {
+                stream_name: stream_name.to_string(),
+                expected_version,
+                message_data: message_data.clone(),
+                position,
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls::{self, messages::Example};
+
+    fn example_write_message_data() -> WriteMessageData {
+        controls::messages::example_message()
+            .to_write_message_data()
+            .expect("conversion to work")
+    }
+
+    #[test]
+    fn should_write_to_a_stream_name() {
+        let substitute_writer = SubstituteWriter::new();
+        let mut writer: Box<dyn Write> = Box::new(substitute_writer.clone());
+        let stream_name = crate::messaging::StreamName::new("example")
+            .and_then(|stream_name| stream_name.with_id("123"))
+            .expect("stream name to build");
+
+        writer
+            .write(&example_write_message_data(), &stream_name, None)
+            .expect("write to work");
+
+        assert_eq!(substitute_writer.written_to("example-123").len(), 1);
+    }
+
+    #[test]
+    fn should_record_each_write() {
+        let mut writer = SubstituteWriter::new();
+        let message_data = example_write_message_data();
+
+        writer
+            .write(&message_data, "example-123", Some(NO_STREAM))
+            .expect("write to work");
+
+        assert_eq!(
+            writer.writes(),
+            vec![Written {
+                stream_name: "example-123".to_string(),
+                expected_version: Some(NO_STREAM),
+                message_data,
+                position: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn should_return_increasing_positions_per_stream() {
+        let mut writer = SubstituteWriter::new();
+        let message_data = example_write_message_data();
+
+        let first = writer.write(&message_data, "example-123", None);
+        let other_stream = writer.write(&message_data, "example-456", None);
+        let second = writer.write(&message_data, "example-123", None);
+
+        assert_eq!(first.expect("write to work"), 0);
+        assert_eq!(other_stream.expect("write to work"), 0);
+        assert_eq!(second.expect("write to work"), 1);
+    }
+
+    #[test]
+    fn should_only_apply_expected_version_to_the_first_message_in_a_batch() {
+        let mut writer = SubstituteWriter::new();
+        let messages = vec![example_write_message_data(), example_write_message_data()];
+
+        let position = writer
+            .write_batch(&messages, "example-123", Some(NO_STREAM))
+            .expect("batch write to work");
+
+        assert_eq!(position, 1);
+        let expected_versions: Vec<_> = writer
+            .writes()
+            .into_iter()
+            .map(|written| written.expected_version)
+            .collect();
+        assert_eq!(expected_versions, vec![Some(NO_STREAM), None]);
+    }
+
+    #[test]
+    fn should_reject_an_empty_batch() {
+        let mut writer = SubstituteWriter::new();
+
+        let result = writer.write_batch(&[], "example-123", None);
+
+        assert!(matches!(result, Err(WriteError::EmptyBatch)));
+        assert!(writer.writes().is_empty());
+    }
+
+    #[test]
+    fn should_find_messages_written_to_a_stream() {
+        let mut writer = SubstituteWriter::new();
+        let message_data = example_write_message_data();
+        let _ = writer.write(&message_data, "example-123", None);
+        let _ = writer.write(&example_write_message_data(), "example-456", None);
+
+        assert_eq!(writer.written_to("example-123"), vec![message_data]);
+        assert!(writer.written_to("example-789").is_empty());
+    }
+
+    #[test]
+    fn should_return_one_message_of_a_type_only_when_exactly_one_was_written() {
+        let mut writer = SubstituteWriter::new();
+        assert_eq!(writer.one_message_of_type::<Example>(), None);
+
+        let _ = writer.write(&example_write_message_data(), "example-123", None);
+        assert_eq!(
+            writer.one_message_of_type::<Example>(),
+            Some(controls::messages::example_message())
+        );
+
+        let _ = writer.write(&example_write_message_data(), "example-123", None);
+        assert_eq!(writer.one_message_of_type::<Example>(), None);
+    }
+
+    #[test]
+    fn should_tell_whether_a_matching_message_was_written() {
+        let mut writer = SubstituteWriter::new();
+        let _ = writer.write(&example_write_message_data(), "example-123", None);
+
+        assert!(writer.written(|written| written.stream_name == "example-123"));
+        assert!(!writer.written(|written| written.expected_version.is_some()));
+    }
+
+    #[test]
+    fn should_share_records_between_clones() {
+        let writer = SubstituteWriter::new();
+        let mut handler_writer = writer.clone();
+
+        let _ = handler_writer.write(&example_write_message_data(), "example-123", None);
+
+        assert_eq!(writer.writes().len(), 1);
+    }
+}
@@ -18,6 +18,12 @@ impl SubstituteRunTime {
     }
 }
 
+impl Default for SubstituteRunTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RunTime for SubstituteRunTime {
     fn sleep(&mut self, duration: Duration) {
         self.run_limit = self.run_limit.map(|limit| limit.saturating_sub(duration));
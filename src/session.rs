@@ -1,30 +1,171 @@
-use crate::settings::Settings;
+use std::error::Error as StdError;
+use std::time::Duration;
 
-use postgres::{types::ToSql, Client, Error as PostgresError, NoTls, Row, ToStatement};
+use crate::back_off::{constant::ConstantBackOff, BackOff};
+use crate::settings::{InvalidSettings, Settings, SettingsError, SslMode};
+
+use postgres::{
+    config::SslMode as PostgresSslMode, types::ToSql, Client, Config, Error as PostgresError, Row,
+    ToStatement, Transaction,
+};
 use thiserror::Error;
 
+pub mod pool;
+#[cfg(feature = "tls")]
+mod tls;
+
+pub use pool::*;
+
+const ENABLE_SQL_CONDITION_QUERY: &str =
+    "SELECT set_config('message_store.sql_condition', 'on', false);";
+
+#[cfg(feature = "tls")]
+type Connector = postgres_native_tls::MakeTlsConnector;
+#[cfg(not(feature = "tls"))]
+type Connector = postgres::NoTls;
+
 #[derive(Error, Debug)]
 pub enum SessionError {
     #[error("Error with database: {0}")]
     PostgresError(#[from] PostgresError),
+    #[error("Unable to enable message_store.sql_condition for the session: {0}")]
+    SqlConditionError(PostgresError),
+    #[error("Error with settings: {0}")]
+    SettingsError(#[from] SettingsError),
+    #[error("{0}")]
+    InvalidSettings(#[from] InvalidSettings),
+    #[error("Unable to set up TLS: {0}")]
+    TlsError(Box<dyn StdError + Send + Sync>),
+    #[error("ssl_mode {0} requires the tls feature")]
+    TlsNotEnabled(SslMode),
+    #[error("Unable to check out a pooled connection: {0}")]
+    PoolError(#[from] r2d2::Error),
+    #[error("Lost the connection to the database: {0}")]
+    ConnectionLost(Box<SessionError>),
 }
 
+impl SessionError {
+    /// Whether the session ran out of reconnect attempts, rather than the query itself failing
+    pub fn is_connection_lost(&self) -> bool {
+        matches!(self, SessionError::ConnectionLost(_))
+    }
+}
+
+/// A connection to Message DB, either its own or checked out of a `SessionPool` for each query.
+/// When the connection is lost a query is retried on a new connection, up to
+/// `Settings::reconnect_attempts` times, before failing with `SessionError::ConnectionLost`.
+/// Writes go through `query_once` so they never run twice.
 pub struct Session {
-    client: Client,
+    connection: Connection,
+    /// Set on every new client connection, as it only applies to the connection it is set on
+    sql_condition: bool,
+    reconnect_attempts: u32,
+    reconnect_back_off: Box<dyn BackOff + Send>,
+    reconnect_count: u64,
+}
+
+enum Connection {
+    Client(Box<ClientConnection>),
+    Pool(SessionPool),
+}
+
+struct ClientConnection {
+    /// `None` once a reconnect has failed
+    client: Option<Client>,
+    config: Config,
+    connector: Connector,
+}
+
+impl ClientConnection {
+    fn is_closed(&self) -> bool {
+        self.client.as_ref().is_none_or(Client::is_closed)
+    }
+
+    // NoTls is Copy but the tls connector is only Clone
+    #[allow(clippy::clone_on_copy)]
+    fn connect(&self) -> Result<Client, PostgresError> {
+        self.config.connect(self.connector.clone())
+    }
 }
 
 impl std::fmt::Debug for Session {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Session {{ client: <hidden> }}")
+        match &self.connection {
+            Connection::Client(_) => write!(f, "Session {{ client: <hidden> }}"),
+            Connection::Pool(pool) => write!(f, "Session {{ pool: {:?} }}", pool),
+        }
     }
 }
 
+pub trait SessionTelemetry {
+    fn record_reconnect(&mut self, attempt: u32, error: &SessionError);
+    fn record_reconnected(&mut self);
+}
+
 impl Session {
     pub fn build() -> Result<Self, SessionError> {
-        let settings = Settings::build();
-        let client = Client::connect(&settings.message_db_url, NoTls)?;
+        Self::build_params(&Settings::build()?)
+    }
 
-        Ok(Self { client })
+    pub fn build_params(settings: &Settings) -> Result<Self, SessionError> {
+        let mut connection = ClientConnection {
+            client: None,
+            config: config(settings)?,
+            connector: connector(settings)?,
+        };
+        connection.client = Some(connection.connect()?);
+
+        Ok(Self::new(
+            Connection::Client(Box::new(connection)),
+            settings.reconnect_attempts,
+            settings.reconnect_interval_ms,
+        ))
+    }
+
+    /// A session sharing the connections of `pool`
+    pub fn pooled(pool: &SessionPool) -> Self {
+        Self::new(
+            Connection::Pool(pool.clone()),
+            pool.reconnect_attempts(),
+            pool.reconnect_interval_ms(),
+        )
+    }
+
+    fn new(connection: Connection, reconnect_attempts: u32, reconnect_interval_ms: u64) -> Self {
+        Self {
+            connection,
+            sql_condition: false,
+            reconnect_attempts,
+            reconnect_back_off: Box::new(ConstantBackOff::new_with_duration(
+                Duration::from_millis(reconnect_interval_ms),
+            )),
+            reconnect_count: 0,
+        }
+    }
+
+    /// Replaces the constant `Settings::reconnect_interval_ms` wait before each reconnect. The
+    /// back-off is asked for a duration with a message count of 0 on every attempt.
+    pub fn with_reconnect_back_off(mut self, back_off: impl BackOff + Send + 'static) -> Self {
+        self.reconnect_back_off = Box::new(back_off);
+        self
+    }
+
+    /// How many times the session has reconnected after losing its connection, not counting
+    /// attempts that failed to connect
+    pub fn reconnect_count(&self) -> u64 {
+        self.reconnect_count
+    }
+
+    /// Message DB raises an error for a query given a `condition` unless
+    /// `message_store.sql_condition` is on for the session
+    pub fn enable_sql_condition(&mut self) -> Result<(), SessionError> {
+        self.sql_condition = true;
+
+        match self.connection {
+            Connection::Client(_) => self.retry(enable_sql_condition),
+            // `SessionPool` enables it on each connection it opens
+            Connection::Pool(_) => Ok(()),
+        }
     }
 
     // TODO: Better way to handle this? Seems odd to "expose" implementation types though what else could I do other then wrap them ...
@@ -36,6 +177,429 @@ impl Session {
     where
         T: ?Sized + ToStatement,
     {
-        self.client.query(query, params).map_err(SessionError::from)
+        self.retry(|client| client.query(query, params).map_err(SessionError::from))
+    }
+
+    /// Runs a statement that must not run twice, such as a write that may have committed just
+    /// before the connection was lost. A lost connection is only replaced before the statement
+    /// runs, losing it while the statement runs is returned as an error.
+    pub fn query_once<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, SessionError>
+    where
+        T: ?Sized + ToStatement,
+    {
+        if let Connection::Client(_) = self.connection {
+            self.retry(|_| Ok(()))?;
+        }
+
+        self.connection
+            .run(self.sql_condition, &mut |client: &mut Client| {
+                client.query(query, params).map_err(SessionError::from)
+            })
+            .map_err(SessionError::from)
+    }
+
+    /// Runs `work` in a transaction that is committed when it returns `Ok` and rolled back otherwise.
+    /// A lost connection is only replaced before the transaction starts, as `work` can't be retried.
+    pub fn transaction<T, E>(
+        &mut self,
+        work: impl FnOnce(&mut SessionTransaction) -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        E: From<SessionError>,
+    {
+        if let Connection::Client(_) = self.connection {
+            self.retry(|_| Ok(()))?;
+        }
+
+        match &mut self.connection {
+            Connection::Client(connection) => transaction(
+                connection
+                    .client
+                    .as_mut()
+                    .expect("the client to be connected"),
+                work,
+            ),
+            Connection::Pool(pool) => transaction(&mut *pool.checkout()?, work),
+        }
+    }
+
+    /// Runs `run` on a connection, reconnecting and running it again when the connection was lost
+    fn retry<T>(
+        &mut self,
+        mut run: impl FnMut(&mut Client) -> Result<T, SessionError>,
+    ) -> Result<T, SessionError> {
+        let mut attempt = 0;
+        let mut reconnecting = self.connection.is_lost();
+
+        loop {
+            let result = self.connection.run(self.sql_condition, &mut run);
+
+            if reconnecting && !matches!(result, Err(RunError::Connect(_))) {
+                self.record_reconnected();
+            }
+
+            match result {
+                Err(RunError::Connect(error) | RunError::Disconnected(error))
+                    if attempt < self.reconnect_attempts =>
+                {
+                    attempt += 1;
+                    reconnecting = true;
+                    self.record_reconnect(attempt, &error);
+                    std::thread::sleep(self.reconnect_back_off.duration(0));
+                }
+                Err(error) => return Err(error.into()),
+                Ok(result) => return Ok(result),
+            }
+        }
+    }
+}
+
+/// Why `Connection::run` failed
+enum RunError {
+    /// No connection could be made, so nothing was run. A pool that can't check out a
+    /// connection in time has already waited and reconnected on its own, so that is `Failed`.
+    Connect(SessionError),
+    /// The connection was lost while running
+    Disconnected(SessionError),
+    Failed(SessionError),
+}
+
+impl RunError {
+    fn new(error: SessionError, client: &Client) -> Self {
+        if client.is_closed() {
+            RunError::Disconnected(error)
+        } else {
+            RunError::Failed(error)
+        }
+    }
+}
+
+impl From<RunError> for SessionError {
+    fn from(error: RunError) -> Self {
+        match error {
+            RunError::Connect(error) | RunError::Disconnected(error) => {
+                SessionError::ConnectionLost(Box::new(error))
+            }
+            RunError::Failed(error) => error,
+        }
+    }
+}
+
+impl Connection {
+    /// Whether the next run has to replace a lost connection first
+    fn is_lost(&self) -> bool {
+        match self {
+            Connection::Client(connection) => connection.is_closed(),
+            Connection::Pool(_) => false,
+        }
+    }
+
+    fn run<T>(
+        &mut self,
+        sql_condition: bool,
+        run: &mut impl FnMut(&mut Client) -> Result<T, SessionError>,
+    ) -> Result<T, RunError> {
+        match self {
+            Connection::Client(connection) => {
+                if connection.is_closed() {
+                    connection.client = None;
+                    let mut client = connection
+                        .connect()
+                        .map_err(|error| RunError::Connect(error.into()))?;
+                    if sql_condition {
+                        enable_sql_condition(&mut client)
+                            .map_err(|error| RunError::new(error, &client))?;
+                    }
+                    connection.client = Some(client);
+                }
+
+                let client = connection
+                    .client
+                    .as_mut()
+                    .expect("the client to be connected");
+                run(client).map_err(|error| RunError::new(error, client))
+            }
+            Connection::Pool(pool) => {
+                let mut client = pool.checkout().map_err(RunError::Failed)?;
+                run(&mut client).map_err(|error| RunError::new(error, &client))
+            }
+        }
+    }
+}
+
+impl SessionTelemetry for Session {
+    fn record_reconnect(&mut self, attempt: u32, error: &SessionError) {
+        log::warn!(
+            "Connection lost, reconnecting (attempt {} of {}): {}",
+            attempt,
+            self.reconnect_attempts,
+            error
+        );
+    }
+
+    fn record_reconnected(&mut self) {
+        self.reconnect_count += 1;
+        log::info!("Reconnected");
+    }
+}
+
+fn enable_sql_condition(client: &mut Client) -> Result<(), SessionError> {
+    client
+        .query(ENABLE_SQL_CONDITION_QUERY, &[])
+        .map_err(SessionError::SqlConditionError)?;
+
+    Ok(())
+}
+
+fn transaction<T, E>(
+    client: &mut Client,
+    work: impl FnOnce(&mut SessionTransaction) -> Result<T, E>,
+) -> Result<T, E>
+where
+    E: From<SessionError>,
+{
+    let transaction = client.transaction().map_err(SessionError::from)?;
+    let mut session_transaction = SessionTransaction { transaction };
+
+    let result = work(&mut session_transaction)?;
+
+    session_transaction
+        .transaction
+        .commit()
+        .map_err(SessionError::from)?;
+
+    Ok(result)
+}
+
+fn config(settings: &Settings) -> Result<Config, SessionError> {
+    let mut config: Config = settings.message_db_url.parse()?;
+    if let Some(ssl_mode) = settings.ssl_mode {
+        config.ssl_mode(postgres_ssl_mode(ssl_mode));
+    }
+
+    Ok(config)
+}
+
+fn postgres_ssl_mode(ssl_mode: SslMode) -> PostgresSslMode {
+    match ssl_mode {
+        SslMode::Disable => PostgresSslMode::Disable,
+        SslMode::Prefer => PostgresSslMode::Prefer,
+        SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => PostgresSslMode::Require,
+    }
+}
+
+#[cfg(feature = "tls")]
+fn connector(settings: &Settings) -> Result<Connector, SessionError> {
+    tls::connector(settings)
+}
+
+#[cfg(not(feature = "tls"))]
+fn connector(settings: &Settings) -> Result<Connector, SessionError> {
+    if let Some(ssl_mode) = settings.ssl_mode.filter(SslMode::requires_tls) {
+        return Err(SessionError::TlsNotEnabled(ssl_mode));
+    }
+
+    Ok(postgres::NoTls)
+}
+
+pub struct SessionTransaction<'a> {
+    transaction: Transaction<'a>,
+}
+
+impl std::fmt::Debug for SessionTransaction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SessionTransaction {{ transaction: <hidden> }}")
+    }
+}
+
+impl SessionTransaction<'_> {
+    pub fn query<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, SessionError>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.transaction
+            .query(query, params)
+            .map_err(SessionError::from)
+    }
+}
+
+#[cfg(all(test, not(feature = "tls")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_require_the_tls_feature_when_tls_is_required() {
+        let mut settings = Settings::new();
+        settings.ssl_mode = Some(SslMode::Require);
+
+        let result = Session::build_params(&settings);
+
+        assert!(matches!(
+            result,
+            Err(SessionError::TlsNotEnabled(SslMode::Require))
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "integration_tests"))]
+mod integration_tests {
+    use super::*;
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn backend_pid(session: &mut Session) -> i32 {
+        let rows = session
+            .query("SELECT pg_backend_pid();", &[])
+            .expect("query to work");
+        rows.first().expect("a row").get(0)
+    }
+
+    fn terminate_backend(pid: i32) {
+        let mut session = Session::build().expect("session to build");
+        session
+            .query("SELECT pg_terminate_backend($1);", &[&pid])
+            .expect("terminate to work");
+    }
+
+    fn fast_reconnect_settings() -> Settings {
+        let mut settings = Settings::build().expect("settings to build");
+        settings.reconnect_interval_ms = 10;
+        settings
+    }
+
+    #[test]
+    fn should_reconnect_and_retry_when_the_connection_is_lost() {
+        init();
+
+        // Arrange
+        let mut session =
+            Session::build_params(&fast_reconnect_settings()).expect("session to build");
+        let pid = backend_pid(&mut session);
+        terminate_backend(pid);
+
+        // Act
+        let new_pid = backend_pid(&mut session);
+
+        // Assert
+        assert_ne!(new_pid, pid);
+        assert_eq!(session.reconnect_count(), 1);
+    }
+
+    #[test]
+    fn should_keep_the_sql_condition_enabled_after_reconnecting() {
+        init();
+
+        // Arrange
+        let mut session =
+            Session::build_params(&fast_reconnect_settings()).expect("session to build");
+        session
+            .enable_sql_condition()
+            .expect("sql condition to be enabled");
+        terminate_backend(backend_pid(&mut session));
+
+        // Act
+        let rows = session
+            .query(
+                "SELECT current_setting('message_store.sql_condition');",
+                &[],
+            )
+            .expect("query to work");
+
+        // Assert
+        let sql_condition: String = rows.first().expect("a row").get(0);
+        assert_eq!(sql_condition, "on");
+    }
+
+    #[test]
+    fn should_fail_once_reconnect_attempts_are_exhausted() {
+        init();
+
+        // Arrange
+        let mut settings = fast_reconnect_settings();
+        settings.reconnect_attempts = 0;
+        let mut session = Session::build_params(&settings).expect("session to build");
+        terminate_backend(backend_pid(&mut session));
+
+        // Act
+        let result = session.query("SELECT 1;", &[]);
+
+        // Assert
+        assert!(matches!(result, Err(SessionError::ConnectionLost(_))));
+        assert_eq!(session.reconnect_count(), 0);
+    }
+
+    #[test]
+    fn should_not_count_failed_attempts_as_reconnects() {
+        init();
+
+        // Arrange
+        let mut settings = fast_reconnect_settings();
+        settings.reconnect_attempts = 2;
+        let mut session = Session::build_params(&settings).expect("session to build");
+        let pid = backend_pid(&mut session);
+        if let Connection::Client(connection) = &mut session.connection {
+            // Nothing listens on port 1, so every reconnect attempt fails
+            connection.config.port(1);
+        }
+        terminate_backend(pid);
+
+        // Act
+        let result = session.query("SELECT 1;", &[]);
+
+        // Assert
+        assert!(matches!(result, Err(SessionError::ConnectionLost(_))));
+        assert_eq!(session.reconnect_count(), 0);
+    }
+
+    #[test]
+    fn should_not_run_a_statement_again_when_the_connection_is_lost_running_it() {
+        init();
+
+        // Arrange
+        let mut session =
+            Session::build_params(&fast_reconnect_settings()).expect("session to build");
+        terminate_backend(backend_pid(&mut session));
+
+        // Act
+        let result = session.query_once("SELECT 1;", &[]);
+
+        // Assert
+        assert!(matches!(result, Err(SessionError::ConnectionLost(_))));
+        let rows = session.query_once("SELECT 1;", &[]).expect("query to work");
+        assert_eq!(rows.len(), 1);
+    }
+
+    /// Restart the Message DB container (e.g. `make reset-mdb`) within a minute of starting this test
+    #[test]
+    #[ignore]
+    fn should_survive_a_database_restart() {
+        init();
+
+        // Arrange
+        let mut settings = Settings::build().expect("settings to build");
+        settings.reconnect_attempts = 60;
+        settings.reconnect_interval_ms = 1_000;
+        let mut session = Session::build_params(&settings).expect("session to build");
+        let pid = backend_pid(&mut session);
+        log::info!("Restart the database now");
+
+        // Act
+        while session.reconnect_count() == 0 {
+            std::thread::sleep(Duration::from_secs(1));
+            backend_pid(&mut session);
+        }
+
+        // Assert
+        assert_ne!(backend_pid(&mut session), pid);
     }
 }
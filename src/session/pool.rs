@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use postgres::{Client, Error as PostgresError};
+use r2d2::{CustomizeConnection, Pool, PooledConnection};
+use r2d2_postgres::PostgresConnectionManager;
+
+use super::{config, connector, Connector, Session, SessionError, ENABLE_SQL_CONDITION_QUERY};
+use crate::settings::Settings;
+
+type Manager = PostgresConnectionManager<Connector>;
+
+/// Connections shared by every `Session` made from it, so many consumers, writers and position
+/// stores don't each hold a connection of their own. Cloning shares the same connections.
+#[derive(Clone)]
+pub struct SessionPool {
+    pool: Pool<Manager>,
+    reconnect_attempts: u32,
+    reconnect_interval_ms: u64,
+}
+
+/// Turns `message_store.sql_condition` on once for each connection the pool opens. Message DB
+/// only uses it when a query has a condition, so every pooled connection can have it on.
+#[derive(Debug)]
+struct EnableSqlCondition;
+
+impl CustomizeConnection<Client, PostgresError> for EnableSqlCondition {
+    fn on_acquire(&self, client: &mut Client) -> Result<(), PostgresError> {
+        client.query(ENABLE_SQL_CONDITION_QUERY, &[])?;
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SessionPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.pool.state();
+        write!(
+            f,
+            "SessionPool {{ connections: {}, idle_connections: {} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+impl SessionPool {
+    pub fn build() -> Result<Self, SessionError> {
+        Self::build_params(&Settings::build()?)
+    }
+
+    /// Opens `pool_min_size` connections up front, failing if they can't be opened within
+    /// `pool_checkout_timeout_ms`
+    pub fn build_params(settings: &Settings) -> Result<Self, SessionError> {
+        settings.validate()?;
+
+        let manager = PostgresConnectionManager::new(config(settings)?, connector(settings)?);
+        let pool = Pool::builder()
+            .min_idle(Some(settings.pool_min_size))
+            .max_size(settings.pool_max_size)
+            .connection_timeout(Duration::from_millis(settings.pool_checkout_timeout_ms))
+            .connection_customizer(Box::new(EnableSqlCondition))
+            .build(manager)?;
+
+        Ok(Self {
+            pool,
+            reconnect_attempts: settings.reconnect_attempts,
+            reconnect_interval_ms: settings.reconnect_interval_ms,
+        })
+    }
+
+    pub fn session(&self) -> Session {
+        Session::pooled(self)
+    }
+
+    pub(super) fn reconnect_attempts(&self) -> u32 {
+        self.reconnect_attempts
+    }
+
+    pub(super) fn reconnect_interval_ms(&self) -> u64 {
+        self.reconnect_interval_ms
+    }
+
+    pub(super) fn checkout(&self) -> Result<PooledConnection<Manager>, SessionError> {
+        Ok(self.pool.get()?)
+    }
+}
+
+#[cfg(all(test, feature = "integration_tests"))]
+mod integration_tests {
+    use super::*;
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn should_share_connections_between_sessions() {
+        init();
+
+        // Arrange
+        let mut settings = Settings::build().expect("settings to build");
+        settings.pool_min_size = 1;
+        settings.pool_max_size = 1;
+        let pool = SessionPool::build_params(&settings).expect("pool to build");
+        let mut session = pool.session();
+        let mut other_session = pool.session();
+
+        // Act
+        let rows = session.query("SELECT 1;", &[]).expect("query to work");
+        let other_rows = other_session
+            .query("SELECT 1;", &[])
+            .expect("query to work");
+
+        // Assert
+        assert_eq!(rows.len(), 1);
+        assert_eq!(other_rows.len(), 1);
+        assert_eq!(pool.pool.state().connections, 1);
+    }
+
+    #[test]
+    fn should_enable_the_sql_condition_on_every_connection() {
+        init();
+
+        // Arrange
+        let mut settings = Settings::build().expect("settings to build");
+        settings.pool_min_size = 2;
+        settings.pool_max_size = 2;
+        let pool = SessionPool::build_params(&settings).expect("pool to build");
+        let _checked_out = pool.checkout().expect("checkout to work");
+
+        // Act
+        let rows = pool
+            .session()
+            .query(
+                "SELECT current_setting('message_store.sql_condition');",
+                &[],
+            )
+            .expect("query to work");
+
+        // Assert
+        let sql_condition: String = rows.first().expect("a row").get(0);
+        assert_eq!(sql_condition, "on");
+    }
+
+    #[test]
+    fn should_fail_when_no_connection_can_be_checked_out_in_time() {
+        init();
+
+        // Arrange
+        let mut settings = Settings::build().expect("settings to build");
+        settings.pool_min_size = 1;
+        settings.pool_max_size = 1;
+        settings.pool_checkout_timeout_ms = 100;
+        // Would be waited between attempts if the timeout was taken for a lost connection
+        settings.reconnect_interval_ms = 60_000;
+        let pool = SessionPool::build_params(&settings).expect("pool to build");
+        let _checked_out = pool.checkout().expect("checkout to work");
+        let mut session = pool.session();
+        let started = std::time::Instant::now();
+
+        // Act
+        let result = session.query("SELECT 1;", &[]);
+
+        // Assert
+        assert!(matches!(result, Err(SessionError::PoolError(_))));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(session.reconnect_count(), 0);
+    }
+}
@@ -1,13 +1,182 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+use thiserror::Error;
+
 const POSITION_UPDATE_INTERVAL_DEFAULT: u64 = 100;
-const MESSAGE_DB_URL_DEFAULT: &'static str = "postgres://message_store@localhost/message_store";
+const MESSAGE_DB_URL_DEFAULT: &str = "postgres://message_store@localhost/message_store";
 
 const BATCH_SIZE_DEFAULT: Option<u64> = None; //1000 for messagedb
 const CORRELATION_DEFAULT: Option<String> = None;
 const CONSUMER_GROUP_MEMBER_DEFAULT: Option<u64> = None;
 const CONSUMER_GROUP_SIZE_DEFAULT: Option<u64> = None;
 const CONDITION_DEFAULT: Option<String> = None;
+const CONSUMER_IDENTIFIER_DEFAULT: Option<String> = None;
+const SSL_MODE_DEFAULT: Option<SslMode> = None;
+const POOL_MIN_SIZE_DEFAULT: u32 = 1;
+const POOL_MAX_SIZE_DEFAULT: u32 = 10;
+const POOL_CHECKOUT_TIMEOUT_MS_DEFAULT: u64 = 30_000;
+const RECONNECT_ATTEMPTS_DEFAULT: u32 = 10;
+const RECONNECT_INTERVAL_MS_DEFAULT: u64 = 1_000;
+const PREFETCH_QUEUE_SIZE_DEFAULT: Option<u64> = None;
+const PREFETCH_POLL_INTERVAL_MS_DEFAULT: u64 = 100;
+
+/// Path of a JSON or TOML settings file, applied before the other environment variables
+pub const SETTINGS_PATH_ENV: &str = "MESSAGE_STORE_SETTINGS_PATH";
+pub const MESSAGE_STORE_URL_ENV: &str = "MESSAGE_STORE_URL";
+pub const POSITION_UPDATE_INTERVAL_ENV: &str = "CONSUMER_POSITION_UPDATE_INTERVAL";
+pub const BATCH_SIZE_ENV: &str = "CONSUMER_BATCH_SIZE";
+pub const CORRELATION_ENV: &str = "CONSUMER_CORRELATION";
+pub const CONSUMER_GROUP_MEMBER_ENV: &str = "CONSUMER_GROUP_MEMBER";
+pub const CONSUMER_GROUP_SIZE_ENV: &str = "CONSUMER_GROUP_SIZE";
+pub const CONDITION_ENV: &str = "CONSUMER_CONDITION";
+pub const CONSUMER_IDENTIFIER_ENV: &str = "CONSUMER_IDENTIFIER";
+pub const SSL_MODE_ENV: &str = "MESSAGE_STORE_SSL_MODE";
+pub const SSL_ROOT_CERT_ENV: &str = "MESSAGE_STORE_SSL_ROOT_CERT";
+pub const SSL_CLIENT_CERT_ENV: &str = "MESSAGE_STORE_SSL_CLIENT_CERT";
+pub const SSL_CLIENT_KEY_ENV: &str = "MESSAGE_STORE_SSL_CLIENT_KEY";
+pub const POOL_MIN_SIZE_ENV: &str = "MESSAGE_STORE_POOL_MIN_SIZE";
+pub const POOL_MAX_SIZE_ENV: &str = "MESSAGE_STORE_POOL_MAX_SIZE";
+pub const POOL_CHECKOUT_TIMEOUT_MS_ENV: &str = "MESSAGE_STORE_POOL_CHECKOUT_TIMEOUT_MS";
+pub const RECONNECT_ATTEMPTS_ENV: &str = "MESSAGE_STORE_RECONNECT_ATTEMPTS";
+pub const RECONNECT_INTERVAL_MS_ENV: &str = "MESSAGE_STORE_RECONNECT_INTERVAL_MS";
+pub const PREFETCH_QUEUE_SIZE_ENV: &str = "CONSUMER_PREFETCH_QUEUE_SIZE";
+pub const PREFETCH_POLL_INTERVAL_MS_ENV: &str = "CONSUMER_PREFETCH_POLL_INTERVAL_MS";
+
+/// Every variable `Settings::build` reads besides `SETTINGS_PATH_ENV`
+const ENV_VARS: [&str; 19] = [
+    MESSAGE_STORE_URL_ENV,
+    POSITION_UPDATE_INTERVAL_ENV,
+    BATCH_SIZE_ENV,
+    CORRELATION_ENV,
+    CONSUMER_GROUP_MEMBER_ENV,
+    CONSUMER_GROUP_SIZE_ENV,
+    CONDITION_ENV,
+    CONSUMER_IDENTIFIER_ENV,
+    SSL_MODE_ENV,
+    SSL_ROOT_CERT_ENV,
+    SSL_CLIENT_CERT_ENV,
+    SSL_CLIENT_KEY_ENV,
+    POOL_MIN_SIZE_ENV,
+    POOL_MAX_SIZE_ENV,
+    POOL_CHECKOUT_TIMEOUT_MS_ENV,
+    RECONNECT_ATTEMPTS_ENV,
+    RECONNECT_INTERVAL_MS_ENV,
+    PREFETCH_QUEUE_SIZE_ENV,
+    PREFETCH_POLL_INTERVAL_MS_ENV,
+];
+
+/// How the session negotiates TLS, named after libpq's `sslmode`. Anything beyond `Prefer`
+/// requires the `tls` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    Disable,
+    Prefer,
+    /// Encrypts without verifying the server certificate
+    Require,
+    /// Verifies the server certificate but not its host name
+    VerifyCa,
+    VerifyFull,
+}
+
+impl SslMode {
+    pub fn requires_tls(&self) -> bool {
+        !matches!(self, SslMode::Disable | SslMode::Prefer)
+    }
+}
+
+impl FromStr for SslMode {
+    type Err = String;
+
+    fn from_str(ssl_mode: &str) -> Result<Self, Self::Err> {
+        match ssl_mode {
+            "disable" => Ok(SslMode::Disable),
+            "prefer" => Ok(SslMode::Prefer),
+            "require" => Ok(SslMode::Require),
+            "verify-ca" => Ok(SslMode::VerifyCa),
+            "verify-full" => Ok(SslMode::VerifyFull),
+            _ => Err(
+                "expected one of disable, prefer, require, verify-ca or verify-full".to_string(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SslMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ssl_mode = match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+        };
+
+        write!(f, "{}", ssl_mode)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("Unable to read settings file {path}: {source}")]
+    FileError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Unable to parse settings file {path}: {reason}")]
+    ParseError { path: PathBuf, reason: String },
+    #[error("Unsupported settings file {0}, expected a .json or .toml file")]
+    UnsupportedFile(PathBuf),
+    #[error("Invalid value {value:?} for {key}: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+    #[error("Environment variable {0} is not valid Unicode")]
+    NotUnicode(String),
+    #[error("{0}")]
+    InvalidSettings(#[from] InvalidSettings),
+}
 
-#[derive(Debug)]
+/// A single inconsistency found by `Settings::validate`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SettingsValidationError {
+    #[error("consumer_group_member is set without consumer_group_size")]
+    ConsumerGroupMemberWithoutSize,
+    #[error("consumer_group_size is set without consumer_group_member")]
+    ConsumerGroupSizeWithoutMember,
+    #[error("consumer_group_size must be greater than zero")]
+    ZeroConsumerGroupSize,
+    #[error("consumer_group_member {member} must be less than consumer_group_size {size}")]
+    ConsumerGroupMemberOutOfRange { member: u64, size: u64 },
+    #[error("batch_size must be greater than zero")]
+    ZeroBatchSize,
+    #[error("position_update_interval must be greater than zero")]
+    ZeroPositionUpdateInterval,
+    #[error("ssl_client_cert and ssl_client_key must be set together")]
+    IncompleteSslClientIdentity,
+    #[error("pool_max_size must be greater than zero")]
+    ZeroPoolMaxSize,
+    #[error("pool_min_size {min} must not be greater than pool_max_size {max}")]
+    PoolMinSizeAboveMax { min: u32, max: u32 },
+    #[error("pool_checkout_timeout_ms must be greater than zero")]
+    ZeroPoolCheckoutTimeout,
+    #[error("prefetch_queue_size must be greater than zero")]
+    ZeroPrefetchQueueSize,
+}
+
+/// Every inconsistency found by `Settings::validate`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid settings: {}", .errors.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+pub struct InvalidSettings {
+    pub errors: Vec<SettingsValidationError>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Settings {
     pub position_update_interval: u64,
     pub message_db_url: String,
@@ -16,6 +185,57 @@ pub struct Settings {
     pub consumer_group_member: Option<u64>,
     pub consumer_group_size: Option<u64>,
     pub condition: Option<String>,
+    /// Distinguishes the position stream of consumers of the same category
+    pub consumer_identifier: Option<String>,
+    /// Overrides any `sslmode` in `message_db_url`
+    pub ssl_mode: Option<SslMode>,
+    /// PEM file of the certificate authorities the server certificate is verified against,
+    /// in addition to the system's
+    pub ssl_root_cert: Option<PathBuf>,
+    /// PEM certificate presented to the server, along with `ssl_client_key`
+    pub ssl_client_cert: Option<PathBuf>,
+    /// Unencrypted PEM private key of `ssl_client_cert`, PKCS #8 or PKCS #1 (`BEGIN RSA PRIVATE KEY`)
+    pub ssl_client_key: Option<PathBuf>,
+    /// Idle connections a `SessionPool` keeps open
+    pub pool_min_size: u32,
+    pub pool_max_size: u32,
+    /// How long checking a connection out of a `SessionPool` waits before failing
+    pub pool_checkout_timeout_ms: u64,
+    /// How many times a `Session` reconnects and retries a query after losing its connection. A
+    /// consumer that still can't fetch afterwards tries again on its next tick.
+    pub reconnect_attempts: u32,
+    /// Wait before each reconnect, unless the session is given its own back-off
+    pub reconnect_interval_ms: u64,
+    /// Fetches messages on a separate subscription thread, holding up to this many messages
+    /// ahead of the handlers. `None` fetches and handles one batch at a time.
+    pub prefetch_queue_size: Option<u64>,
+    /// Wait between the subscription's fetches while there are no new messages
+    pub prefetch_poll_interval_ms: u64,
+}
+
+/// Every setting a file may contain, each one optional so a file only overrides what it lists
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SettingsFile {
+    position_update_interval: Option<u64>,
+    message_db_url: Option<String>,
+    batch_size: Option<u64>,
+    correlation: Option<String>,
+    consumer_group_member: Option<u64>,
+    consumer_group_size: Option<u64>,
+    condition: Option<String>,
+    consumer_identifier: Option<String>,
+    ssl_mode: Option<SslMode>,
+    ssl_root_cert: Option<PathBuf>,
+    ssl_client_cert: Option<PathBuf>,
+    ssl_client_key: Option<PathBuf>,
+    pool_min_size: Option<u32>,
+    pool_max_size: Option<u32>,
+    pool_checkout_timeout_ms: Option<u64>,
+    reconnect_attempts: Option<u32>,
+    reconnect_interval_ms: Option<u64>,
+    prefetch_queue_size: Option<u64>,
+    prefetch_poll_interval_ms: Option<u64>,
 }
 
 impl Settings {
@@ -28,21 +248,543 @@ impl Settings {
             consumer_group_member: CONSUMER_GROUP_MEMBER_DEFAULT,
             consumer_group_size: CONSUMER_GROUP_SIZE_DEFAULT,
             condition: CONDITION_DEFAULT,
+            consumer_identifier: CONSUMER_IDENTIFIER_DEFAULT,
+            ssl_mode: SSL_MODE_DEFAULT,
+            ssl_root_cert: None,
+            ssl_client_cert: None,
+            ssl_client_key: None,
+            pool_min_size: POOL_MIN_SIZE_DEFAULT,
+            pool_max_size: POOL_MAX_SIZE_DEFAULT,
+            pool_checkout_timeout_ms: POOL_CHECKOUT_TIMEOUT_MS_DEFAULT,
+            reconnect_attempts: RECONNECT_ATTEMPTS_DEFAULT,
+            reconnect_interval_ms: RECONNECT_INTERVAL_MS_DEFAULT,
+            prefetch_queue_size: PREFETCH_QUEUE_SIZE_DEFAULT,
+            prefetch_poll_interval_ms: PREFETCH_POLL_INTERVAL_MS_DEFAULT,
         }
     }
 
-    // TODO: impl this
-    pub fn build() -> Self {
-        // TODO: todo!("grab from env and then override default")
+    /// Starts from the defaults, then applies the settings file named by `MESSAGE_STORE_SETTINGS_PATH`
+    /// and then the environment variables, and validates the result. Explicit overrides are
+    /// assigned to the returned settings.
+    pub fn build() -> Result<Self, SettingsError> {
+        let mut settings = Self::new();
 
-        Self {
-            position_update_interval: POSITION_UPDATE_INTERVAL_DEFAULT,
-            message_db_url: MESSAGE_DB_URL_DEFAULT.to_string(),
-            batch_size: BATCH_SIZE_DEFAULT,
-            correlation: CORRELATION_DEFAULT,
-            consumer_group_member: CONSUMER_GROUP_MEMBER_DEFAULT,
-            consumer_group_size: CONSUMER_GROUP_SIZE_DEFAULT,
-            condition: CONDITION_DEFAULT,
+        if let Some(path) = std::env::var_os(SETTINGS_PATH_ENV) {
+            settings.merge_file(path)?;
+        }
+
+        let mut vars = Vec::new();
+        for key in ENV_VARS {
+            if let Some(value) = env_var(key)? {
+                vars.push((key, value));
+            }
+        }
+        settings.merge_env(vars)?;
+
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    /// Applies a `.json` or `.toml` file, only overriding the settings it contains
+    pub fn merge_file(&mut self, path: impl AsRef<Path>) -> Result<(), SettingsError> {
+        let path = path.as_ref();
+        let contents =
+            std::fs::read_to_string(path).map_err(|source| SettingsError::FileError {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        let parse_error = |reason: String| SettingsError::ParseError {
+            path: path.to_path_buf(),
+            reason,
+        };
+
+        let file: SettingsFile = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => {
+                serde_json::from_str(&contents).map_err(|error| parse_error(error.to_string()))?
+            }
+            Some("toml") => {
+                toml::from_str(&contents).map_err(|error| parse_error(error.to_string()))?
+            }
+            _ => return Err(SettingsError::UnsupportedFile(path.to_path_buf())),
+        };
+
+        self.merge(file);
+
+        Ok(())
+    }
+
+    /// Applies the recognized variables of `vars`, ignoring the rest and any that are empty
+    pub fn merge_env<K, V>(
+        &mut self,
+        vars: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<(), SettingsError>
+    where
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let mut file = SettingsFile::default();
+
+        for (key, value) in vars {
+            let key = key.as_ref();
+            let value: String = value.into();
+
+            if value.is_empty() {
+                continue;
+            }
+
+            match key {
+                MESSAGE_STORE_URL_ENV => file.message_db_url = Some(value),
+                POSITION_UPDATE_INTERVAL_ENV => {
+                    file.position_update_interval = Some(parse_value(key, value)?)
+                }
+                BATCH_SIZE_ENV => file.batch_size = Some(parse_value(key, value)?),
+                CORRELATION_ENV => file.correlation = Some(value),
+                CONSUMER_GROUP_MEMBER_ENV => {
+                    file.consumer_group_member = Some(parse_value(key, value)?)
+                }
+                CONSUMER_GROUP_SIZE_ENV => {
+                    file.consumer_group_size = Some(parse_value(key, value)?)
+                }
+                CONDITION_ENV => file.condition = Some(value),
+                CONSUMER_IDENTIFIER_ENV => file.consumer_identifier = Some(value),
+                SSL_MODE_ENV => file.ssl_mode = Some(parse_value(key, value)?),
+                SSL_ROOT_CERT_ENV => file.ssl_root_cert = Some(value.into()),
+                SSL_CLIENT_CERT_ENV => file.ssl_client_cert = Some(value.into()),
+                SSL_CLIENT_KEY_ENV => file.ssl_client_key = Some(value.into()),
+                POOL_MIN_SIZE_ENV => file.pool_min_size = Some(parse_value(key, value)?),
+                POOL_MAX_SIZE_ENV => file.pool_max_size = Some(parse_value(key, value)?),
+                POOL_CHECKOUT_TIMEOUT_MS_ENV => {
+                    file.pool_checkout_timeout_ms = Some(parse_value(key, value)?)
+                }
+                RECONNECT_ATTEMPTS_ENV => file.reconnect_attempts = Some(parse_value(key, value)?),
+                RECONNECT_INTERVAL_MS_ENV => {
+                    file.reconnect_interval_ms = Some(parse_value(key, value)?)
+                }
+                PREFETCH_QUEUE_SIZE_ENV => {
+                    file.prefetch_queue_size = Some(parse_value(key, value)?)
+                }
+                PREFETCH_POLL_INTERVAL_MS_ENV => {
+                    file.prefetch_poll_interval_ms = Some(parse_value(key, value)?)
+                }
+                _ => {}
+            }
+        }
+
+        self.merge(file);
+
+        Ok(())
+    }
+
+    /// Checks the settings are consistent with each other, returning every problem found
+    pub fn validate(&self) -> Result<(), InvalidSettings> {
+        let mut errors = Vec::new();
+
+        match (self.consumer_group_member, self.consumer_group_size) {
+            (Some(_), None) => errors.push(SettingsValidationError::ConsumerGroupMemberWithoutSize),
+            (None, Some(_)) => errors.push(SettingsValidationError::ConsumerGroupSizeWithoutMember),
+            _ => {}
+        }
+
+        if let Some(size) = self.consumer_group_size {
+            if size == 0 {
+                errors.push(SettingsValidationError::ZeroConsumerGroupSize);
+            } else if let Some(member) = self.consumer_group_member.filter(|member| *member >= size)
+            {
+                errors
+                    .push(SettingsValidationError::ConsumerGroupMemberOutOfRange { member, size });
+            }
+        }
+
+        if self.batch_size == Some(0) {
+            errors.push(SettingsValidationError::ZeroBatchSize);
+        }
+
+        if self.position_update_interval == 0 {
+            errors.push(SettingsValidationError::ZeroPositionUpdateInterval);
+        }
+
+        if self.ssl_client_cert.is_some() != self.ssl_client_key.is_some() {
+            errors.push(SettingsValidationError::IncompleteSslClientIdentity);
+        }
+
+        if self.pool_max_size == 0 {
+            errors.push(SettingsValidationError::ZeroPoolMaxSize);
+        } else if self.pool_min_size > self.pool_max_size {
+            errors.push(SettingsValidationError::PoolMinSizeAboveMax {
+                min: self.pool_min_size,
+                max: self.pool_max_size,
+            });
+        }
+
+        if self.pool_checkout_timeout_ms == 0 {
+            errors.push(SettingsValidationError::ZeroPoolCheckoutTimeout);
+        }
+
+        if self.prefetch_queue_size == Some(0) {
+            errors.push(SettingsValidationError::ZeroPrefetchQueueSize);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidSettings { errors })
+        }
+    }
+
+    fn merge(&mut self, file: SettingsFile) {
+        if let Some(position_update_interval) = file.position_update_interval {
+            self.position_update_interval = position_update_interval;
+        }
+        if let Some(message_db_url) = file.message_db_url {
+            self.message_db_url = message_db_url;
+        }
+        self.batch_size = file.batch_size.or(self.batch_size);
+        self.correlation = file.correlation.or(self.correlation.take());
+        self.consumer_group_member = file.consumer_group_member.or(self.consumer_group_member);
+        self.consumer_group_size = file.consumer_group_size.or(self.consumer_group_size);
+        self.condition = file.condition.or(self.condition.take());
+        self.consumer_identifier = file.consumer_identifier.or(self.consumer_identifier.take());
+        self.ssl_mode = file.ssl_mode.or(self.ssl_mode);
+        self.ssl_root_cert = file.ssl_root_cert.or(self.ssl_root_cert.take());
+        self.ssl_client_cert = file.ssl_client_cert.or(self.ssl_client_cert.take());
+        self.ssl_client_key = file.ssl_client_key.or(self.ssl_client_key.take());
+        if let Some(pool_min_size) = file.pool_min_size {
+            self.pool_min_size = pool_min_size;
+        }
+        if let Some(pool_max_size) = file.pool_max_size {
+            self.pool_max_size = pool_max_size;
+        }
+        if let Some(pool_checkout_timeout_ms) = file.pool_checkout_timeout_ms {
+            self.pool_checkout_timeout_ms = pool_checkout_timeout_ms;
+        }
+        if let Some(reconnect_attempts) = file.reconnect_attempts {
+            self.reconnect_attempts = reconnect_attempts;
+        }
+        if let Some(reconnect_interval_ms) = file.reconnect_interval_ms {
+            self.reconnect_interval_ms = reconnect_interval_ms;
+        }
+        self.prefetch_queue_size = file.prefetch_queue_size.or(self.prefetch_queue_size);
+        if let Some(prefetch_poll_interval_ms) = file.prefetch_poll_interval_ms {
+            self.prefetch_poll_interval_ms = prefetch_poll_interval_ms;
         }
     }
 }
+
+fn env_var(key: &str) -> Result<Option<String>, SettingsError> {
+    match std::env::var(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(std::env::VarError::NotUnicode(_)) => Err(SettingsError::NotUnicode(key.to_string())),
+    }
+}
+
+fn parse_value<T>(key: &str, value: String) -> Result<T, SettingsError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|error: T::Err| SettingsError::InvalidValue {
+            key: key.to_string(),
+            value,
+            reason: error.to_string(),
+        })
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_settings_file(extension: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_eventide_settings_{}.{}",
+            uuid::Uuid::new_v4(),
+            extension
+        ));
+        std::fs::write(&path, contents).expect("settings file to be written");
+        path
+    }
+
+    #[test]
+    fn should_accept_the_defaults() {
+        assert_eq!(Settings::new().validate(), Ok(()));
+    }
+
+    #[test]
+    fn should_accept_a_consumer_group() {
+        let mut settings = Settings::new();
+        settings.consumer_group_member = Some(1);
+        settings.consumer_group_size = Some(2);
+
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn should_require_both_consumer_group_settings() {
+        let mut settings = Settings::new();
+        settings.consumer_group_member = Some(0);
+
+        assert_eq!(
+            settings.validate().map_err(|invalid| invalid.errors),
+            Err(vec![
+                SettingsValidationError::ConsumerGroupMemberWithoutSize
+            ])
+        );
+
+        settings.consumer_group_member = None;
+        settings.consumer_group_size = Some(2);
+
+        assert_eq!(
+            settings.validate().map_err(|invalid| invalid.errors),
+            Err(vec![
+                SettingsValidationError::ConsumerGroupSizeWithoutMember
+            ])
+        );
+    }
+
+    #[test]
+    fn should_reject_a_member_outside_the_consumer_group() {
+        let mut settings = Settings::new();
+        settings.consumer_group_member = Some(2);
+        settings.consumer_group_size = Some(2);
+
+        assert_eq!(
+            settings.validate().map_err(|invalid| invalid.errors),
+            Err(vec![
+                SettingsValidationError::ConsumerGroupMemberOutOfRange { member: 2, size: 2 }
+            ])
+        );
+    }
+
+    #[test]
+    fn should_report_every_invalid_setting() {
+        let mut settings = Settings::new();
+        settings.consumer_group_member = Some(0);
+        settings.consumer_group_size = Some(0);
+        settings.batch_size = Some(0);
+        settings.position_update_interval = 0;
+        settings.prefetch_queue_size = Some(0);
+
+        assert_eq!(
+            settings.validate().map_err(|invalid| invalid.errors),
+            Err(vec![
+                SettingsValidationError::ZeroConsumerGroupSize,
+                SettingsValidationError::ZeroBatchSize,
+                SettingsValidationError::ZeroPositionUpdateInterval,
+                SettingsValidationError::ZeroPrefetchQueueSize,
+            ])
+        );
+    }
+
+    #[test]
+    fn should_reject_an_inconsistent_pool() {
+        let mut settings = Settings::new();
+        settings.pool_min_size = 5;
+        settings.pool_max_size = 2;
+        settings.pool_checkout_timeout_ms = 0;
+
+        assert_eq!(
+            settings.validate().map_err(|invalid| invalid.errors),
+            Err(vec![
+                SettingsValidationError::PoolMinSizeAboveMax { min: 5, max: 2 },
+                SettingsValidationError::ZeroPoolCheckoutTimeout,
+            ])
+        );
+
+        settings.pool_max_size = 0;
+        settings.pool_checkout_timeout_ms = 1;
+
+        assert_eq!(
+            settings.validate().map_err(|invalid| invalid.errors),
+            Err(vec![SettingsValidationError::ZeroPoolMaxSize])
+        );
+    }
+
+    #[test]
+    fn should_apply_recognized_environment_variables() {
+        let mut settings = Settings::new();
+
+        settings
+            .merge_env([
+                (
+                    MESSAGE_STORE_URL_ENV,
+                    "postgres://other@localhost/message_store",
+                ),
+                (BATCH_SIZE_ENV, "10"),
+                (CONSUMER_GROUP_MEMBER_ENV, "0"),
+                (CONSUMER_GROUP_SIZE_ENV, "2"),
+                (CONSUMER_IDENTIFIER_ENV, "some_consumer"),
+                (POOL_MAX_SIZE_ENV, "4"),
+                (RECONNECT_ATTEMPTS_ENV, "3"),
+                ("UNRELATED", "value"),
+            ])
+            .expect("environment to apply");
+
+        assert_eq!(
+            settings.message_db_url,
+            "postgres://other@localhost/message_store"
+        );
+        assert_eq!(settings.batch_size, Some(10));
+        assert_eq!(settings.consumer_group_member, Some(0));
+        assert_eq!(settings.consumer_group_size, Some(2));
+        assert_eq!(
+            settings.consumer_identifier.as_deref(),
+            Some("some_consumer")
+        );
+        assert_eq!(settings.pool_max_size, 4);
+        assert_eq!(settings.reconnect_attempts, 3);
+        assert_eq!(
+            settings.position_update_interval,
+            POSITION_UPDATE_INTERVAL_DEFAULT
+        );
+    }
+
+    #[test]
+    fn should_ignore_empty_environment_variables() {
+        let mut settings = Settings::new();
+        settings.batch_size = Some(10);
+
+        settings
+            .merge_env([(BATCH_SIZE_ENV, "")])
+            .expect("environment to apply");
+
+        assert_eq!(settings.batch_size, Some(10));
+    }
+
+    #[test]
+    fn should_reject_invalid_numbers() {
+        let mut settings = Settings::new();
+
+        let result = settings.merge_env([(BATCH_SIZE_ENV, "ten")]);
+
+        assert!(matches!(
+            result,
+            Err(SettingsError::InvalidValue { ref key, ref value, .. }) if key == BATCH_SIZE_ENV && value == "ten"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_reject_environment_variables_that_are_not_unicode() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let key = "RUSTY_EVENTIDE_TEST_NOT_UNICODE";
+        std::env::set_var(key, std::ffi::OsStr::from_bytes(&[0x66, 0x6f, 0x80]));
+
+        let result = env_var(key);
+
+        assert!(matches!(result, Err(SettingsError::NotUnicode(ref name)) if name == key));
+        assert!(matches!(env_var("RUSTY_EVENTIDE_TEST_NOT_SET"), Ok(None)));
+        std::env::remove_var(key);
+    }
+
+    #[test]
+    fn should_apply_ssl_environment_variables() {
+        let mut settings = Settings::new();
+
+        settings
+            .merge_env([
+                (SSL_MODE_ENV, "verify-full"),
+                (SSL_ROOT_CERT_ENV, "/etc/ssl/root.crt"),
+            ])
+            .expect("environment to apply");
+
+        assert_eq!(settings.ssl_mode, Some(SslMode::VerifyFull));
+        assert_eq!(
+            settings.ssl_root_cert,
+            Some(PathBuf::from("/etc/ssl/root.crt"))
+        );
+
+        let result = settings.merge_env([(SSL_MODE_ENV, "always")]);
+
+        assert!(matches!(result, Err(SettingsError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn should_require_both_the_client_certificate_and_key() {
+        let mut settings = Settings::new();
+        settings.ssl_client_cert = Some(PathBuf::from("client.crt"));
+
+        assert_eq!(
+            settings.validate().map_err(|invalid| invalid.errors),
+            Err(vec![SettingsValidationError::IncompleteSslClientIdentity])
+        );
+    }
+
+    #[test]
+    fn should_apply_a_json_file() {
+        let path = write_settings_file(
+            "json",
+            r#"{ "batch_size": 10, "correlation": "someCorrelation" }"#,
+        );
+        let mut settings = Settings::new();
+
+        settings.merge_file(&path).expect("file to apply");
+
+        assert_eq!(settings.batch_size, Some(10));
+        assert_eq!(settings.correlation.as_deref(), Some("someCorrelation"));
+        assert_eq!(settings.message_db_url, MESSAGE_DB_URL_DEFAULT);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_apply_a_toml_file() {
+        let path = write_settings_file(
+            "toml",
+            "position_update_interval = 5\nconsumer_group_size = 3\nssl_mode = \"require\"\n",
+        );
+        let mut settings = Settings::new();
+
+        settings.merge_file(&path).expect("file to apply");
+
+        assert_eq!(settings.position_update_interval, 5);
+        assert_eq!(settings.consumer_group_size, Some(3));
+        assert_eq!(settings.ssl_mode, Some(SslMode::Require));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_reject_unknown_settings_in_a_file() {
+        let path = write_settings_file("json", r#"{ "batch_sise": 10 }"#);
+        let mut settings = Settings::new();
+
+        let result = settings.merge_file(&path);
+
+        assert!(matches!(result, Err(SettingsError::ParseError { .. })));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_reject_unsupported_files() {
+        let path = write_settings_file("yaml", "batch_size: 10");
+        let mut settings = Settings::new();
+
+        let result = settings.merge_file(&path);
+
+        assert!(matches!(result, Err(SettingsError::UnsupportedFile(_))));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_let_the_environment_override_the_file() {
+        let path = write_settings_file("json", r#"{ "batch_size": 10, "consumer_group_size": 2 }"#);
+        let mut settings = Settings::new();
+
+        settings.merge_file(&path).expect("file to apply");
+        settings
+            .merge_env([(BATCH_SIZE_ENV, "20")])
+            .expect("environment to apply");
+
+        assert_eq!(settings.batch_size, Some(20));
+        assert_eq!(settings.consumer_group_size, Some(2));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -2,7 +2,7 @@
 use rusty_eventide::settings::Settings;
 use rusty_eventide::{messaging::HandleError, *};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct EventHandler;
 
 impl EventHandler {
@@ -49,4 +49,38 @@ mod test {
         consumer.stop();
         assert!(consumer.stopped());
     }
+
+    #[test]
+    fn should_start_a_consumer_with_a_consumer_identifier() {
+        let mut settings = Settings::new();
+        settings.consumer_identifier = Some("some_consumer".to_string());
+
+        let mut consumer = Consumer::build_params("category", settings)
+            .add_handler(EventHandler::build())
+            .start();
+
+        assert!(consumer.started());
+        consumer.stop();
+        assert!(consumer.stopped());
+    }
+
+    #[test]
+    fn should_start_consumers_sharing_a_session_pool() {
+        let settings = Settings::build().expect("settings to build");
+        let pool = session::SessionPool::build_params(&settings).expect("pool to build");
+
+        let mut consumer = Consumer::build_pooled("category", settings.clone(), &pool)
+            .add_handler(EventHandler::build())
+            .start();
+        let mut other_consumer = Consumer::build_pooled("other_category", settings, &pool)
+            .add_handler(EventHandler::build())
+            .start();
+
+        assert!(consumer.started());
+        assert!(other_consumer.started());
+        consumer.stop();
+        other_consumer.stop();
+        assert!(consumer.stopped());
+        assert!(other_consumer.stopped());
+    }
 }